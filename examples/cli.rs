@@ -1,57 +1,141 @@
-use std::{env, fs, time::Duration};
+use std::{env, fs, process::ExitCode, time::Duration};
 
-use spn::{SPN2CaptureRequestOptParams, SPN2CaptureStatus, SPN2Client};
+use spn::{
+    CaptureHandle, Error, SPN2CaptureRequestOptParams, SPN2CaptureStatus, SPN2Client, TimelineEntry,
+};
 use tokio::time;
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() {
-    let args: Vec<String> = env::args().skip(1).collect();
-    let api_access_key = args
-        .get(0)
-        .expect("first arg: <api_access_key>")
-        .to_string();
+async fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let json_errors = args.iter().any(|arg| arg == "--json-errors");
+    args.retain(|arg| arg != "--json-errors");
+    match run(&args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if json_errors => {
+            let error = serde_json::json!({ "code": e.code(), "message": e.to_string() });
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            if let Some(suggestion) = e.suggestion() {
+                eprintln!("hint: {suggestion}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: &[String]) -> Result<(), Error> {
+    let api_access_key = args.first().expect("first arg: <api_access_key>");
     let api_secret_file = args
         .get(1)
         .expect("second arg: <path to file containing api secret>");
-    let url = args.get(2).expect("third arg: <url>");
     let api_secret = fs::read_to_string(api_secret_file)
         .expect("failed to read api secret file")
         .trim()
         .to_string();
-    let client = SPN2Client::new(api_access_key, api_secret, Duration::from_secs(5))
-        .expect("failed to create spn2 client");
-    let user_status = client.get_user_status().await.unwrap();
-    println!("user status: {user_status:?}");
-    let capture_resp = client
+    let client = SPN2Client::builder()
+        .credentials(api_access_key, &api_secret)
+        .timeout(Duration::from_secs(5))
+        .record_timelines(true)
+        .build()?;
+    match args.get(2).map(String::as_str) {
+        Some("config") => {
+            assert_eq!(
+                Some("show"),
+                args.get(3).map(String::as_str),
+                "usage: config show"
+            );
+            let config = serde_json::to_string_pretty(&client.effective_config())
+                .expect("config is serializable");
+            println!("{config}");
+            Ok(())
+        }
+        Some("status") => {
+            let job_id = args.get(3).expect("fourth arg: <job_id>").parse()?;
+            let follow = args.get(4).is_some_and(|arg| arg == "--follow");
+            let capture = client.handle_for(job_id, "", &SPN2CaptureRequestOptParams::default());
+            if follow {
+                follow_capture(&client, &capture).await
+            } else {
+                println!("{}", capture.status().await?);
+                Ok(())
+            }
+        }
+        Some(url) => capture(&client, url).await,
+        None => panic!(
+            "third arg: <url>, status <job_id> [--follow] or config show, optionally --json-errors"
+        ),
+    }
+}
+
+async fn capture(client: &SPN2Client, url: &str) -> Result<(), Error> {
+    let user_status = client.get_user_status().await?;
+    println!("user status: {user_status}");
+    let capture = client
         .request_capture(url, &SPN2CaptureRequestOptParams::default())
-        .await
-        .expect("failed to get capture response");
-    println!("job_id: {}", capture_resp.job_id);
-    let user_status = client.get_user_status().await.unwrap();
-    println!("user status: {user_status:?}");
+        .await?;
+    println!("job_id: {}", capture.job_id());
+    let user_status = client.get_user_status().await?;
+    println!("user status: {user_status}");
     loop {
-        let status = client
-            .get_capture_status(&capture_resp.job_id)
-            .await
-            .expect("failed to get capture status");
-        match status {
-            SPN2CaptureStatus::Pending { resources } => {
+        match capture.status().await? {
+            SPN2CaptureStatus::Pending { resources, .. } => {
                 println!("PENDING");
                 println!("resources: {resources:#?}");
                 time::sleep(Duration::from_secs(2)).await;
             }
             e @ SPN2CaptureStatus::Error { .. } => {
-                println!("ERROR: {e:?}");
+                println!("ERROR: {e}");
                 break;
             }
             s @ SPN2CaptureStatus::Success { .. } => {
-                println!("SUCCESS: {s:?}");
+                println!("SUCCESS: {s}");
+                break;
+            }
+            s => {
+                println!("UNKNOWN: {s}");
                 break;
             }
         }
     }
-    let user_status = client.get_user_status().await.unwrap();
-    println!("user status: {user_status:?}");
-    let system_status = client.get_system_status().await.unwrap();
-    println!("system status: {system_status:?}");
+    println!("timeline:");
+    print_timeline(&client.job_timeline(capture.job_id()), 0);
+    let user_status = client.get_user_status().await?;
+    println!("user status: {user_status}");
+    let system_status = client.get_system_status().await?;
+    println!("system status: {system_status}");
+    Ok(())
+}
+
+/// Poll the capture until it finished, printing its timeline as it changes
+async fn follow_capture(client: &SPN2Client, capture: &CaptureHandle<'_>) -> Result<(), Error> {
+    let mut printed = 0;
+    loop {
+        let status = capture.status().await?;
+        let timeline = client.job_timeline(capture.job_id());
+        print_timeline(&timeline, printed);
+        printed = timeline.len();
+        if !matches!(status, SPN2CaptureStatus::Pending { .. }) {
+            println!("{status}");
+            return Ok(());
+        }
+        time::sleep(Duration::from_secs(2)).await;
+    }
+}
+
+/// Print the timeline entries from the given index on, relative to the first entry
+fn print_timeline(timeline: &[TimelineEntry], from: usize) {
+    let Some(first) = timeline.first() else {
+        return;
+    };
+    for entry in &timeline[from.min(timeline.len())..] {
+        let elapsed = entry
+            .observed_at
+            .duration_since(first.observed_at)
+            .unwrap_or_default();
+        println!("+{:>7.1}s  {:?}", elapsed.as_secs_f64(), entry.event);
+    }
 }
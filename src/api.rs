@@ -0,0 +1,55 @@
+use std::future::Future;
+
+use crate::{
+    Error, JobId, SPN2CaptureRequestOptParams, SPN2CaptureResponse, SPN2CaptureStatus, SPN2Client,
+    SPN2SystemStatus, SPN2UserStatus,
+};
+
+/// The operations of the SPN2 API
+///
+/// Implemented by [`SPN2Client`], which sends the requests, and by
+/// [`RecordingClient`](crate::RecordingClient), which only records them.
+/// Workflow code that is generic over this trait can therefore be dry-run.
+pub trait SpnApi {
+    /// Issue a capture request for the given URL
+    fn submit_capture(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> impl Future<Output = Result<SPN2CaptureResponse, Error>> + Send;
+
+    /// Get the current status of a capture job
+    fn get_capture_status(
+        &self,
+        job_id: &JobId,
+    ) -> impl Future<Output = Result<SPN2CaptureStatus, Error>> + Send;
+
+    /// Get the current status of the user
+    fn get_user_status(&self) -> impl Future<Output = Result<SPN2UserStatus, Error>> + Send;
+
+    /// Get the current status of the SPN system
+    fn get_system_status(&self) -> impl Future<Output = Result<SPN2SystemStatus, Error>> + Send;
+}
+
+impl SpnApi for SPN2Client {
+    async fn submit_capture(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Result<SPN2CaptureResponse, Error> {
+        let handle = self.request_capture(url, opt_params).await?;
+        Ok(handle.into_response())
+    }
+
+    async fn get_capture_status(&self, job_id: &JobId) -> Result<SPN2CaptureStatus, Error> {
+        SPN2Client::get_capture_status(self, job_id).await
+    }
+
+    async fn get_user_status(&self) -> Result<SPN2UserStatus, Error> {
+        SPN2Client::get_user_status(self).await
+    }
+
+    async fn get_system_status(&self) -> Result<SPN2SystemStatus, Error> {
+        SPN2Client::get_system_status(self).await
+    }
+}
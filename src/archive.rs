@@ -0,0 +1,372 @@
+use std::{
+    collections::VecDeque,
+    future::{poll_fn, Future},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    challenge::CHALLENGE_PREFIX_LEN, CaptureError, Credentials, CredentialsCheck, Error,
+    RetryPolicy, SPN2CaptureRequestOptParams, SPN2Client, Snapshot, StandardRetryPolicy,
+    WaitOptions,
+};
+
+/// Archive the page at the given URL right now, returning the URL of its snapshot
+///
+/// Covers the common case in one call: a client with the recommended retry
+/// policy captures the URL with the default parameters, waits at most
+/// `deadline` for the capture job and then verifies that the snapshot exists
+/// in the Wayback Machine. Use an [`SPN2Client`] for anything more.
+///
+/// Fails with [`CaptureError::NotVerified`] if the capture succeeded but its
+/// snapshot can not be found.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// # async fn run() -> Result<(), spn::CaptureError> {
+/// let credentials = spn::Credentials::new("access", "secret");
+/// let wayback_url =
+///     spn::archive("https://example.com/", credentials, Duration::from_secs(300)).await?;
+/// println!("archived at {wayback_url}");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn archive(
+    url: &str,
+    credentials: Credentials,
+    deadline: Duration,
+) -> Result<String, CaptureError> {
+    let client = archive_client(credentials)?;
+    let options = ArchiveOptions {
+        deadline,
+        ..Default::default()
+    };
+    archive_with(&client, url, &options).await
+}
+
+/// Options for [`archive_all`]
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    /// How many URLs are captured at the same time
+    ///
+    /// SPN limits the concurrent captures of an account, see
+    /// [`SPN2UserStatus::available`](crate::SPN2UserStatus::available).
+    pub concurrency: usize,
+    /// How long to wait at most for the capture of each URL
+    pub deadline: Duration,
+    /// The capture parameters used for every URL
+    pub opt_params: SPN2CaptureRequestOptParams,
+    /// Limits the retries of all requests of the batch together, if set
+    pub retry_budget: Option<RetryBudget>,
+    /// Compare each snapshot with the live page, failing below this similarity
+    ///
+    /// Fails captures that saved e.g. a cookie wall instead of the content with
+    /// [`CaptureError::LiveMismatch`]. Fetches every page twice, so it is off
+    /// by default, see [`DEFAULT_MIN_SIMILARITY`](crate::DEFAULT_MIN_SIMILARITY).
+    pub min_live_similarity: Option<f64>,
+    /// Check each snapshot for bot challenges, e.g. Cloudflare's browser check
+    ///
+    /// Fails captures that saved a challenge instead of the page with
+    /// [`CaptureError::BlockedByBotProtection`]. Fetches the start of every
+    /// snapshot, so it is off by default.
+    pub detect_bot_challenges: bool,
+}
+
+/// A limit on the retries of a whole batch
+///
+/// Once the budget is spent, failed requests are not retried anymore, so a
+/// bad day degrades the batch instead of retrying forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryBudget {
+    /// How many retries the batch may use in total
+    pub max_retries: u32,
+    /// How many retries the batch may use within any minute, if limited
+    pub max_retries_per_minute: Option<u32>,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 3,
+            deadline: Duration::from_secs(5 * 60),
+            opt_params: SPN2CaptureRequestOptParams::default(),
+            retry_budget: None,
+            min_live_similarity: None,
+            detect_bot_challenges: false,
+        }
+    }
+}
+
+/// The result of [`archive_all`]
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct BatchSummary {
+    /// The archived URLs with the Wayback URLs of their snapshots, in input order
+    pub archived: Vec<(String, String)>,
+    /// The URLs that could not be archived with the reason, in input order
+    pub failed: Vec<(String, CaptureError)>,
+    /// The failed URLs that were denied a retry by the retry budget, in input order
+    pub budget_exhausted: Vec<String>,
+}
+
+impl BatchSummary {
+    /// Check whether every URL was archived
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Archive the pages at the given URLs, like [`archive`] does for a single URL
+///
+/// The URLs are captured concurrently by one client with the recommended
+/// retry policy. The credentials are checked once before the batch starts,
+/// rejected credentials fail the whole batch with
+/// [`Error::AuthFailed`]. Other failures are collected per URL in the summary.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() -> Result<(), spn::CaptureError> {
+/// let credentials = spn::Credentials::new("access", "secret");
+/// let urls = ["https://example.com/", "https://example.org/"];
+/// let summary = spn::archive_all(urls, credentials, &Default::default()).await?;
+/// for (url, error) in &summary.failed {
+///     eprintln!("failed to archive {url}: {error}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn archive_all<I>(
+    urls: I,
+    credentials: Credentials,
+    options: &ArchiveOptions,
+) -> Result<BatchSummary, CaptureError>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let client = archive_client(credentials)?;
+    let queue: VecDeque<_> = urls.into_iter().map(Into::into).enumerate().collect();
+    if queue.is_empty() {
+        return Ok(BatchSummary::default());
+    }
+    // other failures of the check are left to the captures, which retry them
+    if let Ok(CredentialsCheck::Invalid { .. }) = client.validate_credentials().await {
+        return Err(CaptureError::SubmitFailed(Error::AuthFailed));
+    }
+    let budget = options.retry_budget.map(|budget| {
+        Arc::new(BatchBudget {
+            inner: StandardRetryPolicy::recommended(),
+            budget,
+            state: Mutex::default(),
+        })
+    });
+    let queue = Mutex::new(queue);
+    let results = Mutex::new(Vec::new());
+    let worker = || async {
+        loop {
+            let next = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front();
+            let Some((index, url)) = next else {
+                break;
+            };
+            let (result, exhausted) = match &budget {
+                Some(budget) => {
+                    // a policy per URL tells which URL was denied a retry
+                    let policy = Arc::new(TrackedPolicy {
+                        budget: budget.clone(),
+                        denied: AtomicBool::new(false),
+                    });
+                    let client = client.with_retry_policy(policy.clone());
+                    let result = archive_with(&client, &url, options).await;
+                    let exhausted = result.is_err() && policy.denied.load(Ordering::Relaxed);
+                    (result, exhausted)
+                }
+                None => (archive_with(&client, &url, options).await, false),
+            };
+            results
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((index, url, result, exhausted));
+        }
+    };
+    let mut workers: Vec<_> = (0..options.concurrency.max(1))
+        .map(|_| Box::pin(worker()))
+        .collect();
+    poll_fn(|cx| {
+        workers.retain_mut(|worker| worker.as_mut().poll(cx).is_pending());
+        match workers.is_empty() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    })
+    .await;
+    let mut results = std::mem::take(&mut *results.lock().unwrap_or_else(|e| e.into_inner()));
+    results.sort_by_key(|(index, ..)| *index);
+    let mut summary = BatchSummary::default();
+    for (_, url, result, exhausted) in results {
+        if exhausted {
+            summary.budget_exhausted.push(url.clone());
+        }
+        match result {
+            Ok(wayback_url) => summary.archived.push((url, wayback_url)),
+            Err(error) => summary.failed.push((url, error)),
+        }
+    }
+    Ok(summary)
+}
+
+/// The retry budget shared by all URLs of a batch, with the policy it limits
+#[derive(Debug)]
+struct BatchBudget {
+    inner: StandardRetryPolicy,
+    budget: RetryBudget,
+    state: Mutex<BudgetState>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    used: u32,
+    recent: VecDeque<Instant>,
+}
+
+impl BatchBudget {
+    /// Take a retry from the budget, returning whether one was left
+    fn take(&self) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            state.recent.pop_front();
+        }
+        let rate_exceeded = self
+            .budget
+            .max_retries_per_minute
+            .is_some_and(|max| state.recent.len() >= max as usize);
+        if state.used >= self.budget.max_retries || rate_exceeded {
+            return false;
+        }
+        state.used += 1;
+        state.recent.push_back(now);
+        true
+    }
+}
+
+/// The retry policy of one URL of a batch, remembering whether the budget denied a retry
+#[derive(Debug)]
+struct TrackedPolicy {
+    budget: Arc<BatchBudget>,
+    denied: AtomicBool,
+}
+
+impl RetryPolicy for TrackedPolicy {
+    fn retry_delay(&self, error: &Error, retry: u32, previous: Duration) -> Option<Duration> {
+        let delay = self.budget.inner.retry_delay(error, retry, previous)?;
+        if !self.budget.take() {
+            self.denied.store(true, Ordering::Relaxed);
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+/// Build the client used by the one-shot helpers
+fn archive_client(credentials: Credentials) -> Result<SPN2Client, CaptureError> {
+    SPN2Client::builder()
+        .with_credentials(credentials)
+        .retry_policy(StandardRetryPolicy::recommended())
+        .build()
+        .map_err(CaptureError::SubmitFailed)
+}
+
+/// Capture the URL, wait for the capture and verify that the snapshot exists
+async fn archive_with(
+    client: &SPN2Client,
+    url: &str,
+    options: &ArchiveOptions,
+) -> Result<String, CaptureError> {
+    let wait_opts = WaitOptions {
+        deadline: Some(options.deadline),
+        ..Default::default()
+    };
+    let outcome = client
+        .capture_and_wait(url, &options.opt_params, &wait_opts)
+        .await?;
+    let snapshot = client
+        .head_snapshot(&outcome.status)
+        .await
+        .map_err(|source| CaptureError::TrackingFailed {
+            job_id: outcome.job_id.clone(),
+            source,
+        })?;
+    let wayback_url = match (snapshot, &outcome.wayback_url) {
+        (Some(snapshot), Some(wayback_url)) if snapshot.exists() => wayback_url.clone(),
+        _ => return Err(CaptureError::NotVerified(Box::new(outcome))),
+    };
+    if options.detect_bot_challenges {
+        let snapshot = client
+            .fetch_snapshot_range(&outcome.status, ..CHALLENGE_PREFIX_LEN)
+            .await
+            .map_err(|source| CaptureError::TrackingFailed {
+                job_id: outcome.job_id.clone(),
+                source,
+            })?;
+        if let Some(challenge) = snapshot.as_ref().and_then(Snapshot::bot_challenge) {
+            return Err(CaptureError::BlockedByBotProtection(
+                Box::new(outcome),
+                challenge,
+            ));
+        }
+    }
+    if let Some(min_similarity) = options.min_live_similarity {
+        // an unreachable live page says nothing about the snapshot
+        if let Ok(Some(comparison)) = client.compare_with_live(&outcome.status).await {
+            if comparison.is_mismatch(min_similarity) {
+                return Err(CaptureError::LiveMismatch(Box::new(outcome), comparison));
+            }
+        }
+    }
+    Ok(wayback_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn archive_nothing() {
+        let credentials = Credentials::new("access", "secret");
+        let summary = archive_all(Vec::<String>::new(), credentials, &Default::default())
+            .await
+            .unwrap();
+        assert!(summary.is_complete());
+        assert!(summary.archived.is_empty());
+    }
+
+    #[test]
+    fn spend_retry_budget() {
+        let policy = BatchBudget {
+            inner: StandardRetryPolicy::recommended(),
+            budget: RetryBudget {
+                max_retries: 3,
+                max_retries_per_minute: Some(2),
+            },
+            state: Mutex::default(),
+        };
+        assert!(policy.take());
+        assert!(policy.take());
+        assert!(!policy.take());
+        policy.state.lock().unwrap().recent.clear();
+        assert!(policy.take());
+        assert!(!policy.take());
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A strategy for the delays between repeated attempts
+///
+/// Used for the delays between status polls, see [`crate::WaitOptions::backoff`],
+/// between retries, see [`crate::StandardRetryPolicy::with_backoff`], and
+/// between system status checks, see
+/// [`crate::SPN2ClientBuilder::pause_on_system_issues`].
+pub trait Backoff: fmt::Debug + Send + Sync {
+    /// Get the delay before the given attempt
+    ///
+    /// Attempts are counted from 1, `previous` is the delay before the
+    /// previous attempt, or zero for the first attempt.
+    fn delay(&self, attempt: u32, previous: Duration) -> Duration;
+
+    /// Get the serializable configuration of the backoff
+    ///
+    /// Returns `None` for custom backoffs, which can not be serialized.
+    fn to_config(&self) -> Option<BackoffConfig> {
+        None
+    }
+}
+
+/// The configuration of one of the built-in backoffs, tagged with its kind
+///
+/// # Examples
+///
+/// ```
+/// let config: spn::BackoffConfig =
+///     serde_json::from_str(r#"{"kind":"fixed","delay":{"secs":5,"nanos":0}}"#).unwrap();
+/// assert_eq!(spn::BackoffConfig::Fixed(spn::FixedBackoff {
+///     delay: std::time::Duration::from_secs(5),
+/// }), config);
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BackoffConfig {
+    /// A [`FixedBackoff`]
+    Fixed(FixedBackoff),
+    /// An [`ExponentialBackoff`]
+    Exponential(ExponentialBackoff),
+    /// A [`DecorrelatedJitterBackoff`]
+    DecorrelatedJitter(DecorrelatedJitterBackoff),
+    /// A [`FibonacciBackoff`]
+    Fibonacci(FibonacciBackoff),
+    /// A [`ScheduleBackoff`]
+    Schedule(ScheduleBackoff),
+}
+
+impl BackoffConfig {
+    /// Create the configured backoff
+    pub fn into_backoff(self) -> Arc<dyn Backoff> {
+        match self {
+            BackoffConfig::Fixed(backoff) => Arc::new(backoff),
+            BackoffConfig::Exponential(backoff) => Arc::new(backoff),
+            BackoffConfig::DecorrelatedJitter(backoff) => Arc::new(backoff),
+            BackoffConfig::Fibonacci(backoff) => Arc::new(backoff),
+            BackoffConfig::Schedule(backoff) => Arc::new(backoff),
+        }
+    }
+}
+
+/// Always wait the same delay
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedBackoff {
+    /// The delay between attempts
+    pub delay: Duration,
+}
+
+impl Backoff for FixedBackoff {
+    fn delay(&self, _attempt: u32, _previous: Duration) -> Duration {
+        self.delay
+    }
+
+    fn to_config(&self) -> Option<BackoffConfig> {
+        Some(BackoffConfig::Fixed(*self))
+    }
+}
+
+/// Multiply the delay with every attempt, up to a cap
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ExponentialBackoff {
+    /// The delay before the first attempt
+    pub base: Duration,
+    /// The factor the delay grows by with every attempt
+    pub multiplier: f64,
+    /// The maximum delay
+    pub cap: Duration,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            multiplier: 2.0,
+            cap: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff for ExponentialBackoff {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.base.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.cap)
+            .min(self.cap)
+    }
+
+    fn to_config(&self) -> Option<BackoffConfig> {
+        Some(BackoffConfig::Exponential(*self))
+    }
+}
+
+/// Pick a random delay between the base and three times the previous delay, up to a cap
+///
+/// Spreads out attempts of many concurrent clients, see
+/// <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecorrelatedJitterBackoff {
+    /// The minimum delay
+    pub base: Duration,
+    /// The maximum delay
+    pub cap: Duration,
+}
+
+impl Backoff for DecorrelatedJitterBackoff {
+    fn delay(&self, _attempt: u32, previous: Duration) -> Duration {
+        let upper = previous.max(self.base).saturating_mul(3);
+        let range = upper.saturating_sub(self.base);
+        let jitter = range.mul_f64(random_fraction());
+        (self.base + jitter).min(self.cap)
+    }
+
+    fn to_config(&self) -> Option<BackoffConfig> {
+        Some(BackoffConfig::DecorrelatedJitter(*self))
+    }
+}
+
+/// Grow the delay along the Fibonacci sequence, up to a cap
+///
+/// Grows slower than doubling, e.g. 1s, 1s, 2s, 3s, 5s, 8s for a base of 1s.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FibonacciBackoff {
+    /// The delay before the first two attempts
+    pub base: Duration,
+    /// The maximum delay
+    pub cap: Duration,
+}
+
+impl Backoff for FibonacciBackoff {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Duration {
+        let (mut a, mut b) = (1u32, 1u32);
+        for _ in 1..attempt {
+            (a, b) = (b, a.saturating_add(b));
+        }
+        self.base.saturating_mul(a).min(self.cap)
+    }
+
+    fn to_config(&self) -> Option<BackoffConfig> {
+        Some(BackoffConfig::Fibonacci(*self))
+    }
+}
+
+/// Follow a fixed schedule of delays, repeating the last one
+///
+/// The default schedule, 2s, 5s and then 10s, is used for polling the status
+/// of capture jobs: quick jobs are noticed early without spamming the API
+/// while slow jobs are pending. An empty schedule waits one second, so that
+/// it never turns polling into a busy loop.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleBackoff {
+    /// The delays before the first attempts, the last one is repeated
+    pub delays: Vec<Duration>,
+}
+
+impl Default for ScheduleBackoff {
+    fn default() -> Self {
+        Self {
+            delays: vec![
+                Duration::from_secs(2),
+                Duration::from_secs(5),
+                Duration::from_secs(10),
+            ],
+        }
+    }
+}
+
+impl Backoff for ScheduleBackoff {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Duration {
+        let index = (attempt.max(1) - 1) as usize;
+        self.delays
+            .get(index)
+            .or(self.delays.last())
+            .copied()
+            .unwrap_or(EMPTY_SCHEDULE_DELAY)
+    }
+
+    fn to_config(&self) -> Option<BackoffConfig> {
+        Some(BackoffConfig::Schedule(self.clone()))
+    }
+}
+
+/// The delay of an empty [`ScheduleBackoff`]
+const EMPTY_SCHEDULE_DELAY: Duration = Duration::from_secs(1);
+
+/// Get a random number in `[0, 1)`, good enough for jitter
+pub(crate) fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    fn delays(backoff: &dyn Backoff, attempts: u32) -> Vec<Duration> {
+        let mut previous = Duration::ZERO;
+        (1..=attempts)
+            .map(|attempt| {
+                previous = backoff.delay(attempt, previous);
+                previous
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exponential_and_fibonacci_are_capped() {
+        let exponential = ExponentialBackoff {
+            base: SEC,
+            multiplier: 2.0,
+            cap: 10 * SEC,
+        };
+        assert_eq!(
+            vec![SEC, 2 * SEC, 4 * SEC, 8 * SEC, 10 * SEC],
+            delays(&exponential, 5)
+        );
+        let fibonacci = FibonacciBackoff {
+            base: SEC,
+            cap: 6 * SEC,
+        };
+        assert_eq!(
+            vec![SEC, SEC, 2 * SEC, 3 * SEC, 5 * SEC, 6 * SEC],
+            delays(&fibonacci, 6)
+        );
+        assert_eq!(10 * SEC, exponential.delay(u32::MAX, Duration::ZERO));
+    }
+
+    #[test]
+    fn schedule_repeats_last_delay() {
+        assert_eq!(
+            vec![2 * SEC, 5 * SEC, 10 * SEC, 10 * SEC],
+            delays(&ScheduleBackoff::default(), 4)
+        );
+        let empty = ScheduleBackoff { delays: Vec::new() };
+        assert_eq!(SEC, empty.delay(1, Duration::ZERO));
+    }
+
+    #[test]
+    fn decorrelated_jitter_stays_in_bounds() {
+        let jitter = DecorrelatedJitterBackoff {
+            base: SEC,
+            cap: 20 * SEC,
+        };
+        let mut previous = Duration::ZERO;
+        for attempt in 1..50 {
+            let delay = jitter.delay(attempt, previous);
+            assert!(delay >= SEC && delay <= 20 * SEC);
+            assert!(delay <= previous.max(SEC) * 3);
+            previous = delay;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Why a page was archived, e.g. `cited-in:case-123`
+///
+/// A basis consists of a kind and a reference, separated by a colon. It is
+/// not sent to the API, but kept with the capture and included in its
+/// [`CaptureOutcome`](crate::CaptureOutcome), so that records management can
+/// demonstrate why each page was archived.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct CaptureBasis {
+    kind: String,
+    reference: String,
+}
+
+impl CaptureBasis {
+    /// Create a basis from its kind, e.g. `cited-in`, and its reference, e.g. `case-123`
+    ///
+    /// Fails if either part is empty or the kind contains a colon.
+    pub fn new(kind: &str, reference: &str) -> Result<Self, Error> {
+        if kind.is_empty() || reference.is_empty() || kind.contains(':') {
+            return Err(Error::InvalidBasis(format!("{kind}:{reference}")));
+        }
+        Ok(Self {
+            kind: kind.to_string(),
+            reference: reference.to_string(),
+        })
+    }
+
+    /// Get the kind of the basis, e.g. `collection-policy`
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Get the reference of the basis, e.g. `news`
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+}
+
+impl FromStr for CaptureBasis {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, reference) = s
+            .split_once(':')
+            .ok_or_else(|| Error::InvalidBasis(s.to_string()))?;
+        Self::new(kind.trim(), reference.trim())
+    }
+}
+
+impl TryFrom<String> for CaptureBasis {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CaptureBasis> for String {
+    fn from(basis: CaptureBasis) -> Self {
+        basis.to_string()
+    }
+}
+
+impl fmt::Display for CaptureBasis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind, self.reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_basis() {
+        let basis: CaptureBasis = "collection-policy:news:politics".parse().unwrap();
+        assert_eq!("collection-policy", basis.kind());
+        assert_eq!("news:politics", basis.reference());
+        assert_eq!(
+            "\"collection-policy:news:politics\"",
+            serde_json::to_string(&basis).unwrap()
+        );
+        assert!("case-123".parse::<CaptureBasis>().is_err());
+        assert!(":case-123".parse::<CaptureBasis>().is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use reqwest::{
+    dns::Resolve,
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, USER_AGENT},
+    Client, ClientBuilder, Proxy,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inflight::InFlightCaptures, limits::LimitMode, retry::SystemStatusPause,
+    sessions::SessionLimiter, timeline::Timelines, Auth, Backoff, CircuitBreaker, Credentials,
+    DeserializeMode, Error, ProxyConfig, RetryPolicy, SPN2CaptureRequestOptParams, SPN2Client,
+    StandardRetryPolicy,
+};
+
+/// The default User-Agent of requests to the SPN API
+pub const DEFAULT_USER_AGENT: &str = concat!("spn/", env!("CARGO_PKG_VERSION"));
+
+/// The default timeout for requests to the SPN API
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A builder to configure and construct a [`SPN2Client`]
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let client = spn::SPN2Client::builder()
+///     .credentials("access", "secret")
+///     .timeout(Duration::from_secs(10))
+///     .default_capture_params(spn::SPN2CaptureRequestOptParams {
+///         capture_all: true,
+///         ..Default::default()
+///     })
+///     .build()
+///     .expect("failed to create spn2 client");
+/// ```
+#[derive(Default)]
+pub struct SPN2ClientBuilder {
+    credentials: Option<Credentials>,
+    anonymous: bool,
+    http_client: Option<Client>,
+    proxies: Vec<Proxy>,
+    proxy_configs: Vec<ProxyConfig>,
+    timeout: Option<Duration>,
+    http_settings: HttpSettings,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    system_status_pause: Option<SystemStatusPause>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    limit_mode: LimitMode,
+    coalesce_captures: bool,
+    session_poll_interval: Option<Duration>,
+    deserialize_mode: DeserializeMode,
+    record_timelines: bool,
+    default_capture_params: SPN2CaptureRequestOptParams,
+    headers: HeaderMap,
+}
+
+impl SPN2ClientBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the credentials, the S3 API keys of an archive.org account
+    pub fn credentials(self, api_access_key: &str, api_secret: &str) -> Self {
+        self.with_credentials(Credentials::new(api_access_key, api_secret))
+    }
+
+    /// Set the credentials
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Allow building the client without credentials
+    ///
+    /// An anonymous client can only use the operations that do not require
+    /// authentication, the others fail with [`Error::CredentialsRequired`].
+    /// Credentials can still be added later with [`SPN2Client::set_credentials`].
+    pub fn anonymous(mut self) -> Self {
+        self.anonymous = true;
+        self
+    }
+
+    /// Use the given HTTP client instead of building a new one
+    ///
+    /// This allows sharing a client, and its connection pool, proxy and TLS
+    /// settings, with the rest of an application.
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Send requests through the given proxy
+    ///
+    /// Can be called multiple times to add proxies for different schemes. Has
+    /// no effect if an HTTP client is given with [`Self::http_client`], its
+    /// proxies are used instead. Use [`SPN2Client::with_proxy`] to switch the
+    /// proxy of a built client.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Send requests through the configured proxy
+    ///
+    /// Like [`Self::proxy`], but the proxy is described by its URL, basic
+    /// authentication and no-proxy list. An invalid proxy URL makes
+    /// [`Self::build`] fail.
+    pub fn proxy_config(mut self, config: ProxyConfig) -> Self {
+        self.proxy_configs.push(config);
+        self
+    }
+
+    /// Set the timeout for requests to the SPN API, defaults to [`DEFAULT_TIMEOUT`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for establishing connections
+    ///
+    /// Allows failing fast on dead networks while the request timeout, see
+    /// [`Self::timeout`], stays long enough for slow responses. Has no effect
+    /// if an HTTP client is given with [`Self::http_client`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http_settings.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how many idle connections per host are kept in the pool
+    ///
+    /// Has no effect if an HTTP client is given with [`Self::http_client`].
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.http_settings.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set how long idle connections are kept in the pool
+    ///
+    /// Has no effect if an HTTP client is given with [`Self::http_client`].
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.http_settings.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set whether responses may be gzip compressed, defaults to true
+    ///
+    /// Status responses with many resources compress well. Has no effect if
+    /// an HTTP client is given with [`Self::http_client`].
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.http_settings.gzip = Some(enable);
+        self
+    }
+
+    /// Set whether responses may be brotli compressed, defaults to true
+    ///
+    /// Has no effect if an HTTP client is given with [`Self::http_client`].
+    #[cfg(feature = "brotli")]
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.http_settings.brotli = Some(enable);
+        self
+    }
+
+    /// Set which HTTP version is used, defaults to negotiating it
+    ///
+    /// Has no effect if an HTTP client is given with [`Self::http_client`].
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http_settings.http_version = version;
+        self
+    }
+
+    /// Set which IP version is used to connect, defaults to any
+    ///
+    /// Restricting connections to IPv4 helps on networks with broken IPv6
+    /// routes to web.archive.org. Has no effect if an HTTP client is given
+    /// with [`Self::http_client`].
+    ///
+    /// The version is enforced by binding to an unspecified local address of
+    /// that family. With [`IpVersion::Any`], reqwest 0.11 falls back from the
+    /// first address family to the other after a fixed delay of 300 ms
+    /// ("happy eyeballs"). reqwest does not expose that delay, so it can not
+    /// be configured here; pick a single IP version instead if the fallback
+    /// is too slow.
+    pub fn ip_version(mut self, version: IpVersion) -> Self {
+        self.http_settings.ip_version = version;
+        self
+    }
+
+    /// Resolve the given domain to a fixed address instead of using DNS
+    ///
+    /// Can be called multiple times to add addresses, which are tried in
+    /// order. A port of 0 is replaced by the port of the request URL. Has no
+    /// effect if an HTTP client is given with [`Self::http_client`].
+    pub fn resolve(mut self, domain: &str, addr: SocketAddr) -> Self {
+        match self
+            .http_settings
+            .dns_overrides
+            .iter_mut()
+            .find(|(d, _)| d == domain)
+        {
+            Some((_, addrs)) => addrs.push(addr),
+            None => {
+                self.http_settings
+                    .dns_overrides
+                    .push((domain.to_string(), vec![addr]));
+            }
+        }
+        self
+    }
+
+    /// Use the given DNS resolver instead of the system resolver
+    ///
+    /// Addresses set with [`Self::resolve`] still take precedence. Has no
+    /// effect if an HTTP client is given with [`Self::http_client`].
+    pub fn dns_resolver<R: Resolve + 'static>(mut self, resolver: Arc<R>) -> Self {
+        self.http_settings.dns_resolver = Some(DnsResolver(Arc::new(move |builder| {
+            builder.dns_resolver(resolver.clone())
+        })));
+        self
+    }
+
+    /// Set how failed requests are retried, defaults to never retrying
+    ///
+    /// The policy applies to all requests of the client. Use
+    /// [`StandardRetryPolicy::recommended`] for sensible defaults.
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Pause retries while the SPN system reports issues
+    ///
+    /// When a request failed with a server error or a rate limit, the system
+    /// status is checked before retrying, and while it is not ok the retry
+    /// is paused with delays from the given backoff instead of hammering a
+    /// struggling service. SPN may report issues for hours, so once the next
+    /// delay would exceed `max_wait` in total, the request fails with its
+    /// last error. Off by default.
+    pub fn pause_on_system_issues(
+        mut self,
+        backoff: impl Backoff + 'static,
+        max_wait: Duration,
+    ) -> Self {
+        self.system_status_pause = Some(SystemStatusPause {
+            backoff: Arc::new(backoff),
+            max_wait,
+        });
+        self
+    }
+
+    /// Fail fast while the SPN service is down, see [`CircuitBreaker`]
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(Arc::new(breaker));
+        self
+    }
+
+    /// Set how capture parameters that exceed the documented limits are handled
+    ///
+    /// Defaults to clamping them, see [`crate::limits`].
+    pub fn limit_mode(mut self, mode: LimitMode) -> Self {
+        self.limit_mode = mode;
+        self
+    }
+
+    /// Coalesce identical capture requests that are in flight at the same time
+    ///
+    /// When a URL is requested to be captured while a request for the same
+    /// URL with the same parameters is still being sent, no second request is
+    /// sent and both callers get a handle to the same capture job. URLs are
+    /// compared after normalization, e.g. ignoring the case of the host and
+    /// fragments. Off by default.
+    pub fn coalesce_captures(mut self, coalesce: bool) -> Self {
+        self.coalesce_captures = coalesce;
+        self
+    }
+
+    /// Delay capture requests while all capture sessions of the user are busy
+    ///
+    /// SPN limits the concurrent captures of a user and rejects capture
+    /// requests beyond that with `error:user-session-limit`. With this, the
+    /// available sessions are tracked from the user status, and while none
+    /// are available, capture requests wait, checking the user status again
+    /// at the given interval. Needs credentials. Off by default.
+    pub fn wait_for_sessions(mut self, poll_interval: Duration) -> Self {
+        self.session_poll_interval = Some(poll_interval);
+        self
+    }
+
+    /// Set how strictly API responses are deserialized
+    pub fn deserialize_mode(mut self, mode: DeserializeMode) -> Self {
+        self.deserialize_mode = mode;
+        self
+    }
+
+    /// Set whether the timelines of capture jobs are recorded
+    pub fn record_timelines(mut self, record: bool) -> Self {
+        self.record_timelines = record;
+        self
+    }
+
+    /// Set the capture parameters used by [`SPN2Client::request_capture_with_defaults`]
+    pub fn default_capture_params(mut self, opt_params: SPN2CaptureRequestOptParams) -> Self {
+        self.default_capture_params = opt_params;
+        self
+    }
+
+    /// Add a header that is sent with every request to the SPN API
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Set the User-Agent of requests to the SPN API, defaults to [`DEFAULT_USER_AGENT`]
+    ///
+    /// The Internet Archive asks bots to identify themselves with contact
+    /// information, e.g. `my-archiver/1.0 (+mailto:ops@example.com)`. This is
+    /// unrelated to the `use_user_agent` capture parameter, which sets the
+    /// User-Agent of the capture browser.
+    pub fn user_agent(self, user_agent: HeaderValue) -> Self {
+        self.header(USER_AGENT, user_agent)
+    }
+
+    /// Build the client
+    ///
+    /// Fails if no credentials were set, unless the client is anonymous.
+    pub fn build(mut self) -> Result<SPN2Client, Error> {
+        let auth = match (self.credentials, self.anonymous) {
+            (Some(credentials), _) => Some(Auth::try_from(&credentials)?),
+            (None, true) => None,
+            (None, false) => return Err(Error::CredentialsRequired),
+        };
+        let mut headers = self.headers;
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers
+            .entry(USER_AGENT)
+            .or_insert(HeaderValue::from_static(DEFAULT_USER_AGENT));
+        let http_client = match self.http_client {
+            Some(http_client) => {
+                self.http_settings.custom_client = true;
+                http_client
+            }
+            None => {
+                let configured = self
+                    .proxy_configs
+                    .iter()
+                    .map(ProxyConfig::to_proxy)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let proxies: Vec<_> = self.proxies.into_iter().chain(configured).collect();
+                self.http_settings.proxies = proxies.iter().map(|p| format!("{p:?}")).collect();
+                let builder = self.http_settings.client_builder();
+                proxies
+                    .into_iter()
+                    .fold(builder, |builder, proxy| builder.proxy(proxy))
+                    .build()?
+            }
+        };
+        Ok(SPN2Client {
+            http_client,
+            auth: Arc::new(RwLock::new(auth)),
+            headers,
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            http_settings: self.http_settings,
+            retry_policy: self
+                .retry_policy
+                .unwrap_or_else(|| Arc::new(StandardRetryPolicy::default())),
+            system_status_pause: self.system_status_pause,
+            circuit_breaker: self.circuit_breaker,
+            rate_limit_pause: Arc::default(),
+            limit_mode: self.limit_mode,
+            deserialize_mode: self.deserialize_mode,
+            timelines: self
+                .record_timelines
+                .then(|| Arc::new(Timelines::default())),
+            submitted: Arc::default(),
+            in_flight: self
+                .coalesce_captures
+                .then(|| Arc::new(InFlightCaptures::default())),
+            sessions: self
+                .session_poll_interval
+                .map(|interval| Arc::new(SessionLimiter::new(interval))),
+            default_capture_params: self.default_capture_params,
+        })
+    }
+}
+
+/// Which HTTP version is used for requests to the SPN API
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HttpVersion {
+    /// Negotiate the version with the server, preferring HTTP/2 if offered
+    #[default]
+    Negotiate,
+    /// Only use HTTP/1.1
+    Http1Only,
+    /// Use HTTP/2 without negotiating it first
+    Http2PriorKnowledge,
+}
+
+/// Which IP version is used to connect to the SPN API
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IpVersion {
+    /// Use IPv4 or IPv6, whichever the resolved addresses allow
+    #[default]
+    Any,
+    /// Only connect over IPv4
+    V4,
+    /// Only connect over IPv6
+    V6,
+}
+
+impl IpVersion {
+    /// Get the local address to bind to, which restricts the address family
+    fn local_address(self) -> Option<IpAddr> {
+        match self {
+            IpVersion::Any => None,
+            IpVersion::V4 => Some(Ipv4Addr::UNSPECIFIED.into()),
+            IpVersion::V6 => Some(Ipv6Addr::UNSPECIFIED.into()),
+        }
+    }
+}
+
+/// A custom DNS resolver, kept as a function that installs it on a client builder
+#[derive(Clone)]
+pub(crate) struct DnsResolver(Arc<dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync>);
+
+impl fmt::Debug for DnsResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DnsResolver")
+    }
+}
+
+/// Settings of an HTTP client built by the SPN client itself
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpSettings {
+    /// Whether the HTTP client was given instead of built from these settings
+    pub(crate) custom_client: bool,
+    /// The proxies of the HTTP client, as Debug output which leaves out credentials
+    pub(crate) proxies: Vec<String>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) http_version: HttpVersion,
+    pub(crate) ip_version: IpVersion,
+    pub(crate) dns_overrides: Vec<(String, Vec<SocketAddr>)>,
+    pub(crate) dns_resolver: Option<DnsResolver>,
+    #[cfg(feature = "gzip")]
+    pub(crate) gzip: Option<bool>,
+    #[cfg(feature = "brotli")]
+    pub(crate) brotli: Option<bool>,
+}
+
+impl HttpSettings {
+    /// Create a builder for an HTTP client with these settings
+    pub(crate) fn client_builder(&self) -> ClientBuilder {
+        let mut builder = Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(addr) = self.ip_version.local_address() {
+            builder = builder.local_address(addr);
+        }
+        for (domain, addrs) in &self.dns_overrides {
+            builder = builder.resolve_to_addrs(domain, addrs);
+        }
+        if let Some(DnsResolver(install)) = &self.dns_resolver {
+            builder = install(builder);
+        }
+        #[cfg(feature = "gzip")]
+        if let Some(enable) = self.gzip {
+            builder = builder.gzip(enable);
+        }
+        #[cfg(feature = "brotli")]
+        if let Some(enable) = self.brotli {
+            builder = builder.brotli(enable);
+        }
+        match self.http_version {
+            HttpVersion::Negotiate => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Snapshot;
+
+/// How many bytes at the start of a snapshot are enough to recognize a bot challenge
+pub(crate) const CHALLENGE_PREFIX_LEN: u64 = 64 * 1024;
+
+/// An interstitial of a bot protection that was archived instead of the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BotChallenge {
+    /// A Cloudflare browser check or block page
+    Cloudflare,
+    /// A CAPTCHA, e.g. reCAPTCHA or hCaptcha
+    Captcha,
+    /// The challenge of another bot management service, e.g. DataDome or PerimeterX
+    BotManager,
+}
+
+impl fmt::Display for BotChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BotChallenge::Cloudflare => "Cloudflare challenge",
+            BotChallenge::Captcha => "CAPTCHA",
+            BotChallenge::BotManager => "bot management challenge",
+        })
+    }
+}
+
+/// Markers in the body of challenge pages, checked in order
+const BODY_MARKERS: &[(&str, BotChallenge)] = &[
+    ("cf-browser-verification", BotChallenge::Cloudflare),
+    ("cf_chl_opt", BotChallenge::Cloudflare),
+    ("/cdn-cgi/challenge-platform/", BotChallenge::Cloudflare),
+    ("<title>just a moment...</title>", BotChallenge::Cloudflare),
+    (
+        "<title>attention required! | cloudflare</title>",
+        BotChallenge::Cloudflare,
+    ),
+    ("captcha-delivery.com", BotChallenge::BotManager),
+    ("px-captcha", BotChallenge::BotManager),
+    ("g-recaptcha", BotChallenge::Captcha),
+    ("h-captcha", BotChallenge::Captcha),
+    ("hcaptcha.com/1/api.js", BotChallenge::Captcha),
+    ("challenges.cloudflare.com/turnstile", BotChallenge::Captcha),
+];
+
+impl Snapshot {
+    /// Detect whether the snapshot shows a bot challenge instead of the page
+    ///
+    /// Recognizes common interstitials like Cloudflare's browser check and
+    /// CAPTCHA pages by their markup. The start of the body is enough, see
+    /// [`SPN2Client::fetch_snapshot_range`](crate::SPN2Client::fetch_snapshot_range).
+    pub fn bot_challenge(&self) -> Option<BotChallenge> {
+        // the Wayback Machine prefixes the archived headers in raw playback
+        let mitigated = ["cf-mitigated", "x-archive-orig-cf-mitigated"]
+            .iter()
+            .filter_map(|name| self.headers.get(*name))
+            .any(|value| value.as_bytes().eq_ignore_ascii_case(b"challenge"));
+        if mitigated {
+            return Some(BotChallenge::Cloudflare);
+        }
+        let body = String::from_utf8_lossy(&self.body).to_lowercase();
+        BODY_MARKERS
+            .iter()
+            .find(|(marker, _)| body.contains(marker))
+            .map(|(_, challenge)| *challenge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{
+        header::{HeaderMap, HeaderValue},
+        StatusCode,
+    };
+
+    use super::*;
+
+    fn snapshot(headers: HeaderMap, body: &str) -> Snapshot {
+        Snapshot {
+            original_url: "https://example.com/".to_string(),
+            timestamp: "20221002124400".to_string(),
+            status: StatusCode::OK,
+            headers,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn detect_challenges() {
+        let cloudflare = "<html><head><title>Just a moment...</title></head></html>";
+        assert_eq!(
+            Some(BotChallenge::Cloudflare),
+            snapshot(HeaderMap::new(), cloudflare).bot_challenge()
+        );
+        let captcha = r#"<form><div class="g-recaptcha" data-sitekey="x"></div></form>"#;
+        assert_eq!(
+            Some(BotChallenge::Captcha),
+            snapshot(HeaderMap::new(), captcha).bot_challenge()
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-archive-orig-cf-mitigated",
+            HeaderValue::from_static("challenge"),
+        );
+        assert_eq!(
+            Some(BotChallenge::Cloudflare),
+            snapshot(headers, "").bot_challenge()
+        );
+        let article = "<html><head><title>News</title></head><body>Hello</body></html>";
+        assert_eq!(None, snapshot(HeaderMap::new(), article).bot_challenge());
+    }
+}
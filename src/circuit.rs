@@ -0,0 +1,196 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, ErrorClass, SPN2Client, SPN2SystemStatus};
+
+/// Stops sending requests while the SPN service is down
+///
+/// The breaker trips after the given amount of consecutive failures that
+/// hint at an outage, i.e. network errors, timeouts, server errors and rate
+/// limits, or when the system status is critical. While it is open, requests
+/// fail fast with [`Error::CircuitOpen`]. After the cooldown the system status
+/// is probed, and requests resume once it is not critical anymore.
+///
+/// Installed with [`crate::SPN2ClientBuilder::circuit_breaker`].
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    Probing,
+}
+
+/// What to do with a request, depending on the state of the breaker
+#[derive(Debug, PartialEq, Eq)]
+enum Admission {
+    Send,
+    Reject { retry_after: Duration },
+    Probe,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker that trips after the given amount of consecutive failures
+    ///
+    /// Once tripped, it stays open for the cooldown before probing.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Check whether the breaker is open, i.e. requests fail fast
+    pub fn is_open(&self) -> bool {
+        !matches!(*self.lock(), State::Closed { .. })
+    }
+
+    /// Let a request pass, fail it fast or probe the system status first
+    pub(crate) async fn admit(&self, client: &SPN2Client) -> Result<(), Error> {
+        match self.admission() {
+            Admission::Send => Ok(()),
+            Admission::Reject { retry_after } => Err(Error::CircuitOpen { retry_after }),
+            Admission::Probe => {
+                let _guard = ProbeGuard(self);
+                self.probe(client).await
+            }
+        }
+    }
+
+    /// Probe the system status, closing the breaker unless it is critical
+    async fn probe(&self, client: &SPN2Client) -> Result<(), Error> {
+        match client.fetch_system_status().await {
+            Ok(status) if status != SPN2SystemStatus::Critical => {
+                *self.lock() = State::Closed { failures: 0 };
+                Ok(())
+            }
+            _ => {
+                self.trip();
+                Err(Error::CircuitOpen {
+                    retry_after: self.cooldown,
+                })
+            }
+        }
+    }
+
+    /// Record the result of a request
+    pub(crate) fn record(&self, error: Option<&Error>) {
+        let outage = error.is_some_and(|e| {
+            matches!(
+                e.class(),
+                ErrorClass::Network
+                    | ErrorClass::Timeout
+                    | ErrorClass::ServerError
+                    | ErrorClass::RateLimited
+            )
+        });
+        let mut state = self.lock();
+        match *state {
+            State::Closed { failures } if outage => {
+                *state = match failures + 1 {
+                    failures if failures >= self.failure_threshold => State::Open {
+                        until: Instant::now() + self.cooldown,
+                    },
+                    failures => State::Closed { failures },
+                };
+            }
+            State::Closed { .. } if error.is_none() => *state = State::Closed { failures: 0 },
+            _ => {}
+        }
+    }
+
+    /// Open the breaker for the cooldown
+    pub(crate) fn trip(&self) {
+        *self.lock() = State::Open {
+            until: Instant::now() + self.cooldown,
+        };
+    }
+
+    fn admission(&self) -> Admission {
+        let mut state = self.lock();
+        match *state {
+            State::Closed { .. } => Admission::Send,
+            State::Open { until } => match until.checked_duration_since(Instant::now()) {
+                Some(retry_after) => Admission::Reject { retry_after },
+                None => {
+                    *state = State::Probing;
+                    Admission::Probe
+                }
+            },
+            State::Probing => Admission::Reject {
+                retry_after: Duration::ZERO,
+            },
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Opens the breaker again if a probe is dropped before it finished
+///
+/// Otherwise a cancelled probe, e.g. by a timeout, would leave the breaker
+/// probing and rejecting every request forever.
+struct ProbeGuard<'a>(&'a CircuitBreaker);
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if *self.0.lock() == State::Probing {
+            self.0.trip();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn trip_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        let server_error = Error::UnexpectedStatus(StatusCode::SERVICE_UNAVAILABLE);
+        breaker.record(Some(&server_error));
+        breaker.record(None);
+        breaker.record(Some(&server_error));
+        breaker.record(Some(&Error::AuthFailed));
+        assert_eq!(Admission::Send, breaker.admission());
+        breaker.record(Some(&server_error));
+        assert!(breaker.is_open());
+        assert!(matches!(breaker.admission(), Admission::Reject { .. }));
+    }
+
+    #[test]
+    fn probe_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.trip();
+        assert_eq!(Admission::Probe, breaker.admission());
+        assert_eq!(
+            Admission::Reject {
+                retry_after: Duration::ZERO
+            },
+            breaker.admission()
+        );
+    }
+
+    #[test]
+    fn cancelled_probe_reopens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        *breaker.lock() = State::Probing;
+        drop(ProbeGuard(&breaker));
+        assert!(matches!(
+            breaker.admission(),
+            Admission::Reject { retry_after } if retry_after > Duration::from_secs(50)
+        ));
+    }
+}
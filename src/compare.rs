@@ -0,0 +1,142 @@
+use reqwest::{Method, StatusCode};
+
+use crate::{Error, SPN2CaptureStatus, SPN2Client};
+
+/// How many words of each page are compared at most
+const MAX_COMPARED_WORDS: usize = 2000;
+
+/// The similarity below which a snapshot is considered not to show the live page
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.5;
+
+/// A comparison of the live page with its snapshot
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct LiveComparison {
+    /// The HTTP status of the live page
+    pub live_status: StatusCode,
+    /// The HTTP status of the archived response
+    pub snapshot_status: StatusCode,
+    /// How similar the texts of the pages are, from 0 (nothing in common) to 1 (same text)
+    pub similarity: f64,
+}
+
+impl LiveComparison {
+    /// Check whether the snapshot differs too much from the live page
+    ///
+    /// A low similarity usually means that the capture saved a cookie wall or
+    /// a bot check instead of the real content, see [`DEFAULT_MIN_SIMILARITY`].
+    pub fn is_mismatch(&self, min_similarity: f64) -> bool {
+        self.similarity < min_similarity
+    }
+}
+
+impl SPN2Client {
+    /// Compare the snapshot of a successful capture with the live page
+    ///
+    /// Fetches both and compares their text, ignoring markup, scripts and
+    /// styles. The live page may have changed since the capture, so a
+    /// similarity slightly below 1 is normal. Returns `None` if the capture
+    /// did not succeed.
+    pub async fn compare_with_live(
+        &self,
+        status: &SPN2CaptureStatus,
+    ) -> Result<Option<LiveComparison>, Error> {
+        let Some(snapshot) = self.fetch_snapshot(status).await? else {
+            return Ok(None);
+        };
+        let live = self
+            .http_client
+            .request(Method::GET, &snapshot.original_url)
+            .timeout(self.timeout)
+            .send()
+            .await?;
+        let live_status = live.status();
+        let live_body = live.bytes().await?;
+        Ok(Some(LiveComparison {
+            live_status,
+            snapshot_status: snapshot.status,
+            similarity: similarity(
+                &String::from_utf8_lossy(&live_body),
+                &String::from_utf8_lossy(&snapshot.body),
+            ),
+        }))
+    }
+}
+
+/// Compute the similarity of the texts of two pages as a normalized diff ratio
+///
+/// The ratio is twice the number of words in the longest common subsequence
+/// divided by the total number of words, like Python's `difflib` ratio.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = words(a);
+    let b = words(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let mut previous = vec![0u32; b.len() + 1];
+    let mut current = vec![0u32; b.len() + 1];
+    for word in &a {
+        for (j, other) in b.iter().enumerate() {
+            current[j + 1] = if word == other {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    2.0 * f64::from(previous[b.len()]) / (a.len() + b.len()) as f64
+}
+
+/// Split the text of a page into lowercase words, skipping markup, scripts and styles
+fn words(html: &str) -> Vec<String> {
+    let lower = html.to_lowercase();
+    let mut text = String::with_capacity(lower.len());
+    let mut rest = lower.as_str();
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        rest = &rest[start..];
+        // scripts and styles are skipped up to their closing tag, other tags up to their end
+        let skip_until = ["script", "style"]
+            .into_iter()
+            .find(|tag| rest[1..].starts_with(tag))
+            .map_or_else(String::new, |tag| format!("</{tag}"));
+        rest = match rest.find(skip_until.as_str()) {
+            Some(end) => {
+                let rest = &rest[end + skip_until.len()..];
+                rest.find('>').map_or("", |close| &rest[close + 1..])
+            }
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(MAX_COMPARED_WORDS)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_words() {
+        let html = r#"<html><head><style>p { color: red }</style>
+            <script>var x = "<p>";</script></head>
+            <body><p class="a">Hello, <b>World</b>!</p></body></html>"#;
+        assert_eq!(vec!["hello", "world"], words(html));
+    }
+
+    #[test]
+    fn compare_texts() {
+        let article = "<p>The quick brown fox jumps over the lazy dog</p>";
+        assert_eq!(1.0, similarity(article, article));
+        let edited = "<p>The quick brown fox jumps over the sleepy dog</p>";
+        assert!(similarity(article, edited) > 0.8);
+        let wall = "<div>We use cookies. Accept all cookies to continue</div>";
+        assert!(similarity(article, wall) < DEFAULT_MIN_SIMILARITY);
+    }
+}
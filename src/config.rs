@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+use crate::{
+    limits::LimitMode, serialize_duration_secs, DeserializeMode, HttpVersion, IpVersion,
+    SPN2CaptureRequestOptParams, SPN2Client, API_CAPTURE_URL,
+};
+
+/// A snapshot of everything that affects the behavior of a client
+///
+/// Meant to be printed or attached to support requests, it contains no secrets.
+#[derive(Serialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct EffectiveConfig {
+    /// The URL of the capture endpoint
+    pub api_url: String,
+    /// The access key of the credentials, `None` for an anonymous client
+    pub access_key: Option<String>,
+    /// The timeout for requests to the SPN API
+    #[serde(serialize_with = "serialize_duration_secs")]
+    pub timeout: Duration,
+    /// The timeout for establishing connections, if set
+    #[serde(serialize_with = "serialize_opt_duration_secs")]
+    pub connect_timeout: Option<Duration>,
+    /// How many idle connections per host are kept in the pool, if set
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long idle connections are kept in the pool, if set
+    #[serde(serialize_with = "serialize_opt_duration_secs")]
+    pub pool_idle_timeout: Option<Duration>,
+    /// Which HTTP version is used
+    pub http_version: HttpVersion,
+    /// Which IP version is used to connect
+    pub ip_version: IpVersion,
+    /// The domains resolved to fixed addresses instead of using DNS
+    pub dns_overrides: Vec<String>,
+    /// Whether a custom DNS resolver is used
+    pub custom_dns_resolver: bool,
+    /// The proxies requests are sent through, without their credentials
+    pub proxies: Vec<String>,
+    /// Whether the HTTP client was given instead of built by the SPN client
+    ///
+    /// The HTTP settings and proxies above do not apply to a given client.
+    pub custom_http_client: bool,
+    /// How failed requests are retried, as the Debug output of the retry policy
+    pub retry_policy: String,
+    /// How retries are paused while the system reports issues, as Debug output, if enabled
+    pub system_status_pause: Option<String>,
+    /// Whether a circuit breaker is installed
+    pub circuit_breaker: bool,
+    /// How capture parameters that exceed the documented limits are handled
+    pub limit_mode: LimitMode,
+    /// Whether identical capture requests in flight are coalesced
+    pub coalesce_captures: bool,
+    /// How often the user status is checked while no capture sessions are available, if enabled
+    #[serde(serialize_with = "serialize_opt_duration_secs")]
+    pub session_poll_interval: Option<Duration>,
+    /// How strictly API responses are deserialized
+    pub deserialize_mode: DeserializeMode,
+    /// Whether the timelines of capture jobs are recorded
+    pub record_timelines: bool,
+    /// The capture parameters used by [`SPN2Client::request_capture_with_defaults`]
+    ///
+    /// The cookies and the target password are replaced by `***`.
+    pub default_capture_params: SPN2CaptureRequestOptParams,
+    /// The names of the additional headers sent with every request
+    pub headers: Vec<String>,
+    /// The enabled cargo features of this crate
+    pub features: Vec<&'static str>,
+}
+
+impl SPN2Client {
+    /// Get a snapshot of the effective configuration of the client
+    pub fn effective_config(&self) -> EffectiveConfig {
+        EffectiveConfig {
+            api_url: API_CAPTURE_URL.to_string(),
+            access_key: self.auth().as_ref().map(|auth| auth.access_key.clone()),
+            timeout: self.timeout,
+            connect_timeout: self.http_settings.connect_timeout,
+            pool_max_idle_per_host: self.http_settings.pool_max_idle_per_host,
+            pool_idle_timeout: self.http_settings.pool_idle_timeout,
+            http_version: self.http_settings.http_version,
+            ip_version: self.http_settings.ip_version,
+            dns_overrides: self
+                .http_settings
+                .dns_overrides
+                .iter()
+                .map(|(domain, _)| domain.clone())
+                .collect(),
+            custom_dns_resolver: self.http_settings.dns_resolver.is_some(),
+            proxies: self.http_settings.proxies.clone(),
+            custom_http_client: self.http_settings.custom_client,
+            retry_policy: format!("{:?}", self.retry_policy),
+            system_status_pause: self
+                .system_status_pause
+                .as_ref()
+                .map(|pause| format!("{pause:?}")),
+            circuit_breaker: self.circuit_breaker.is_some(),
+            limit_mode: self.limit_mode,
+            coalesce_captures: self.in_flight.is_some(),
+            session_poll_interval: self.sessions.as_ref().map(|s| s.poll_interval()),
+            deserialize_mode: self.deserialize_mode,
+            record_timelines: self.timelines.is_some(),
+            default_capture_params: self.default_capture_params.redacted(),
+            headers: self.header_names(),
+            features: enabled_features(),
+        }
+    }
+
+    /// Get the sorted names of the headers sent with every request
+    fn header_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .headers
+            .keys()
+            .map(|name| name.as_str().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+fn serialize_opt_duration_secs<S>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match d {
+        Some(d) => serialize_duration_secs(d, s),
+        None => s.serialize_none(),
+    }
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    let features: [(&str, bool); 9] = [
+        ("brotli", cfg!(feature = "brotli")),
+        ("chrono", cfg!(feature = "chrono")),
+        ("experimental-params", cfg!(feature = "experimental-params")),
+        ("gzip", cfg!(feature = "gzip")),
+        ("miette", cfg!(feature = "miette")),
+        ("native-tls", cfg!(feature = "native-tls")),
+        ("rustls", cfg!(feature = "rustls")),
+        ("socks", cfg!(feature = "socks")),
+        ("warc", cfg!(feature = "warc")),
+    ];
+    features
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_without_secret() {
+        let client = SPN2Client::builder()
+            .credentials("access", "secret")
+            .timeout(Duration::from_millis(1500))
+            .connect_timeout(Duration::from_secs(2))
+            .default_capture_params(
+                SPN2CaptureRequestOptParams {
+                    target_username: Some("login".to_string()),
+                    target_password: Some("hunter2".to_string()),
+                    ..Default::default()
+                }
+                .with_cookie("session", "cookie-secret"),
+            )
+            .build()
+            .unwrap();
+        let config = serde_json::to_value(client.effective_config()).unwrap();
+        assert_eq!("access", config["access_key"]);
+        assert_eq!(1.5, config["timeout"]);
+        assert_eq!(2.0, config["connect_timeout"]);
+        assert_eq!("lenient", config["deserialize_mode"]);
+        assert_eq!(
+            serde_json::json!(["accept", "user-agent"]),
+            config["headers"]
+        );
+        assert_eq!("any", config["ip_version"]);
+        assert!(config["system_status_pause"].is_null());
+        assert_eq!("login", config["default_capture_params"]["target_username"]);
+        assert_eq!("***", config["default_capture_params"]["target_password"]);
+        assert_eq!("***", config["default_capture_params"]["capture_cookie"]);
+        assert!(!config.to_string().contains("secret"));
+        assert!(!config.to_string().contains("hunter2"));
+        assert!(!format!("{client:?}").contains("hunter2"));
+        assert!(format!("{client:?}").contains("ip_version: Any"));
+        assert!(!format!("{client:?}").contains("\"secret\""));
+        client.set_credentials("rotated", "secret2").unwrap();
+        assert_eq!(
+            Some("rotated"),
+            client.effective_config().access_key.as_deref()
+        );
+    }
+
+    #[test]
+    fn report_dns_settings() {
+        let addr = "207.241.225.186:0".parse().unwrap();
+        let config = SPN2Client::builder()
+            .anonymous()
+            .ip_version(IpVersion::V4)
+            .resolve("web.archive.org", addr)
+            .resolve("web.archive.org", addr)
+            .build()
+            .unwrap()
+            .effective_config();
+        assert_eq!(IpVersion::V4, config.ip_version);
+        assert_eq!(vec!["web.archive.org"], config.dns_overrides);
+        assert!(!config.custom_dns_resolver);
+    }
+
+    #[test]
+    fn report_proxies_without_credentials() {
+        let client = SPN2Client::builder()
+            .anonymous()
+            .proxy_config(
+                crate::ProxyConfig::new("http://proxy.example.com:3128")
+                    .basic_auth("user", "proxy-secret"),
+            )
+            .build()
+            .unwrap();
+        let config = serde_json::to_value(client.effective_config()).unwrap();
+        assert!(config["proxies"][0]
+            .as_str()
+            .unwrap()
+            .contains("proxy.example.com:3128"));
+        assert!(!config.to_string().contains("proxy-secret"));
+        assert_eq!(false, config["custom_http_client"]);
+        let proxy = reqwest::Proxy::all("http://other.example.com:8080").unwrap();
+        let switched = client.with_proxy(proxy).unwrap().effective_config();
+        assert_eq!(1, switched.proxies.len());
+        assert!(switched.proxies[0].contains("other.example.com:8080"));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use reqwest::header::HeaderValue;
+use secrecy::{ExposeSecret, SecretString};
+use zeroize::Zeroizing;
+
+use crate::Error;
+
+/// Credentials for the SPN2 API, the S3 API keys of an archive.org account
+///
+/// The keys can be looked up at <https://archive.org/account/s3.php>.
+///
+/// The secret is zeroized on drop and never appears in Debug output.
+#[derive(Clone)]
+pub struct Credentials {
+    access_key: String,
+    secret: SecretString,
+}
+
+impl Credentials {
+    /// Create credentials from an access key and a secret
+    pub fn new(access_key: impl Into<String>, secret: impl Into<SecretString>) -> Self {
+        Self {
+            access_key: access_key.into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Load the credentials from the config file of the `ia` CLI
+    ///
+    /// The file is located like the `ia` CLI does: `$IA_CONFIG_FILE`,
+    /// `$XDG_CONFIG_HOME/internetarchive/ia.ini`, `~/.config/ia.ini` and
+    /// `~/.ia` are tried in order. The keys are read from the `[s3]` section.
+    /// Fails with [`Error::CredentialsRequired`] if no config file exists.
+    pub fn from_ia_config() -> Result<Self, Error> {
+        let path = ia_config_candidates()
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or(Error::CredentialsRequired)?;
+        Self::from_ia_config_file(path, "s3")
+    }
+
+    /// Load the credentials from the given section of an `ia` config file
+    ///
+    /// The section must contain `access` and `secret` keys, e.g.
+    /// ```ini
+    /// [s3]
+    /// access = ACCESS
+    /// secret = SECRET
+    /// ```
+    /// Passing another section name allows keeping the keys of several
+    /// accounts in one file.
+    pub fn from_ia_config_file(path: impl AsRef<Path>, section: &str) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|source| Error::ReadConfig {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let invalid = |reason: String| Error::InvalidConfig {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let values = ini_section(&contents, section)
+            .ok_or_else(|| invalid(format!("missing section [{section}]")))?;
+        let get = |key: &str| {
+            values
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| *v)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| invalid(format!("missing key {key} in section [{section}]")))
+        };
+        Ok(Self::new(get("access")?, get("secret")?))
+    }
+
+    /// Get the access key
+    pub fn access_key(&self) -> &str {
+        &self.access_key
+    }
+
+    /// Get the value of the Authorization header for these credentials
+    ///
+    /// The header value is marked sensitive, which keeps it out of Debug
+    /// output, but it can not be zeroized.
+    pub(crate) fn header_value(&self) -> Result<HeaderValue, Error> {
+        let value = Zeroizing::new(format!(
+            "LOW {}:{}",
+            self.access_key,
+            self.secret.expose_secret()
+        ));
+        let mut value = HeaderValue::from_str(&value)?;
+        value.set_sensitive(true);
+        Ok(value)
+    }
+}
+
+/// The credentials of a client together with their Authorization header value
+///
+/// Shared behind a lock so that the credentials can be rotated while requests
+/// are in flight.
+pub(crate) struct Auth {
+    pub(crate) access_key: String,
+    pub(crate) header: HeaderValue,
+}
+
+impl TryFrom<&Credentials> for Auth {
+    type Error = Error;
+
+    fn try_from(credentials: &Credentials) -> Result<Self, Self::Error> {
+        Ok(Self {
+            access_key: credentials.access_key.clone(),
+            header: credentials.header_value()?,
+        })
+    }
+}
+
+/// The locations of the `ia` config file, in the order the `ia` CLI tries them
+fn ia_config_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(path) = env::var_os("IA_CONFIG_FILE") {
+        candidates.push(PathBuf::from(path));
+    }
+    let home = env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from);
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+    if let Some(config_home) = config_home {
+        candidates.push(config_home.join("internetarchive").join("ia.ini"));
+    }
+    if let Some(home) = home {
+        candidates.push(home.join(".config").join("ia.ini"));
+        candidates.push(home.join(".ia"));
+    }
+    candidates
+}
+
+/// Get the key-value pairs of a section of an INI file, if it exists
+fn ini_section<'a>(contents: &'a str, section: &str) -> Option<Vec<(&'a str, &'a str)>> {
+    let mut in_section = false;
+    let mut found = false;
+    let mut values = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+            found |= in_section;
+        } else if in_section {
+            if let Some((key, value)) = line.split_once(['=', ':']) {
+                values.push((key.trim(), value.trim()));
+            }
+        }
+    }
+    found.then_some(values)
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key", &self.access_key)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IA_INI: &str = "
+[s3]
+access = abc
+secret = xyz
+
+[cookies]
+logged-in-user = user@example.com
+
+; another account
+[work]
+access: def
+secret: uvw
+";
+
+    #[test]
+    fn parse_ia_config_sections() {
+        assert_eq!(
+            Some(vec![("access", "abc"), ("secret", "xyz")]),
+            ini_section(IA_INI, "s3")
+        );
+        assert_eq!(
+            Some(vec![("access", "def"), ("secret", "uvw")]),
+            ini_section(IA_INI, "work")
+        );
+        assert_eq!(None, ini_section(IA_INI, "general"));
+    }
+
+    #[test]
+    fn redact_secret() {
+        let debug = format!("{:?}", Credentials::new("abc", "xyz"));
+        assert!(debug.contains("abc"));
+        assert!(!debug.contains("xyz"));
+    }
+}
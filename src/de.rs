@@ -0,0 +1,175 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Error, SPN2CaptureResponse, SPN2CaptureStatus, SPN2UserStatus};
+
+/// How strictly API responses are deserialized
+///
+/// The SPN2 response format drifts over time, e.g lists go missing or new
+/// fields are added. Long-running archivers should stay on the lenient
+/// default, strict mode is meant for tests that want to detect such drift.
+///
+/// Neither mode attaches the raw JSON to successfully deserialized
+/// responses. Lenient mode keeps the fields it does not know in the `extra`
+/// maps, and responses that cannot be deserialized at all fail with
+/// [`Error::Deserialize`], which contains the raw body. The complete JSON of
+/// a response is available from the raw methods, e.g.
+/// [`SPN2Client::get_capture_status_raw`](crate::SPN2Client::get_capture_status_raw).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeserializeMode {
+    /// Fail on unknown fields and on missing lists
+    Strict,
+    /// Keep unknown fields in `extra` maps and default missing lists to empty lists
+    #[default]
+    Lenient,
+}
+
+/// The fields a response object is expected to have
+pub(crate) struct Shape {
+    /// All fields that are known
+    known: &'static [&'static str],
+    /// Known fields that are defaulted in lenient mode if missing
+    defaulted: &'static [&'static str],
+}
+
+pub(crate) trait ResponseShape {
+    fn shape(json: &Value) -> Option<Shape>;
+}
+
+impl ResponseShape for SPN2CaptureResponse {
+    fn shape(_: &Value) -> Option<Shape> {
+        Some(Shape {
+            known: &["url", "job_id", "message"],
+            defaulted: &[],
+        })
+    }
+}
+
+impl ResponseShape for SPN2CaptureStatus {
+    fn shape(json: &Value) -> Option<Shape> {
+        let shape = match json.get("status")?.as_str()? {
+            "pending" => Shape {
+                known: &["status", "job_id", "resources"],
+                defaulted: &["resources"],
+            },
+            "error" => Shape {
+                known: &[
+                    "status",
+                    "job_id",
+                    "exception",
+                    "status_ext",
+                    "message",
+                    "resources",
+                ],
+                defaulted: &["resources"],
+            },
+            "success" => Shape {
+                known: &[
+                    "status",
+                    "job_id",
+                    "original_url",
+                    "screenshot",
+                    "timestamp",
+                    "duration_sec",
+                    "resources",
+                    "outlinks",
+                    "counters",
+                    "http_status",
+                    "first_archive",
+                ],
+                defaulted: &["resources", "outlinks"],
+            },
+            _ => return None,
+        };
+        Some(shape)
+    }
+}
+
+impl ResponseShape for SPN2UserStatus {
+    fn shape(_: &Value) -> Option<Shape> {
+        Some(Shape {
+            known: &["available", "processing"],
+            defaulted: &[],
+        })
+    }
+}
+
+/// Deserialize a response body according to the given mode
+pub(crate) fn from_body<T>(body: String, mode: DeserializeMode) -> Result<T, Error>
+where
+    T: DeserializeOwned + ResponseShape,
+{
+    let json = match serde_json::from_str::<Value>(&body) {
+        Ok(json) => json,
+        Err(source) => return Err(Error::Deserialize { source, raw: body }),
+    };
+    if mode == DeserializeMode::Strict {
+        if let Err(msg) = check_strict::<T>(&json) {
+            return Err(Error::InvalidResponse(format!("{msg}: {body}")));
+        }
+    }
+    serde_json::from_value(json).map_err(|source| Error::Deserialize { source, raw: body })
+}
+
+fn check_strict<T: ResponseShape>(json: &Value) -> Result<(), String> {
+    let obj = json.as_object().ok_or("not an object")?;
+    // unknown status tags are reported by the deserializer itself
+    let Some(shape) = T::shape(json) else {
+        return Ok(());
+    };
+    if let Some(unknown) = obj.keys().find(|k| !shape.known.contains(&k.as_str())) {
+        return Err(format!("unknown field `{unknown}`"));
+    }
+    if let Some(missing) = shape.defaulted.iter().find(|k| !obj.contains_key(**k)) {
+        return Err(format!("missing field `{missing}`"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PENDING_WITHOUT_RESOURCES: &str = r#"
+        {
+          "status":"pending",
+          "job_id":"e70f23c7-9eca-4c78-826d-26930564d7c8"
+        }
+        "#;
+
+    const PENDING_WITH_UNKNOWN_FIELD: &str = r#"
+        {
+          "status":"pending",
+          "job_id":"e70f23c7-9eca-4c78-826d-26930564d7c8",
+          "resources": [],
+          "delay": 5
+        }
+        "#;
+
+    #[test]
+    fn lenient_defaults_and_ignores() {
+        for body in [PENDING_WITHOUT_RESOURCES, PENDING_WITH_UNKNOWN_FIELD] {
+            let s: SPN2CaptureStatus = from_body(body.to_string(), DeserializeMode::Lenient)
+                .expect("lenient mode should accept drifted response");
+            assert!(
+                matches!(s, SPN2CaptureStatus::Pending { resources, .. } if resources.is_empty())
+            );
+        }
+        let s: SPN2CaptureStatus = from_body(
+            PENDING_WITH_UNKNOWN_FIELD.to_string(),
+            DeserializeMode::Lenient,
+        )
+        .unwrap();
+        assert!(matches!(s, SPN2CaptureStatus::Pending { extra, .. } if extra["delay"] == 5));
+    }
+
+    #[test]
+    fn strict_rejects_drift() {
+        for body in [PENDING_WITHOUT_RESOURCES, PENDING_WITH_UNKNOWN_FIELD] {
+            let res: Result<SPN2CaptureStatus, _> =
+                from_body(body.to_string(), DeserializeMode::Strict);
+            assert!(matches!(res, Err(Error::InvalidResponse(_))));
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    fmt, io,
+    path::PathBuf,
+    time::{Duration, SystemTimeError},
+};
+
+use reqwest::{
+    header::{InvalidHeaderValue, RETRY_AFTER},
+    Response, StatusCode,
+};
+
+use crate::{limits::LimitViolation, ErrorClass, JobId, SPN2CaptureStatus};
+
+/// Errors that may occur when constructing the client and sending requests
+///
+/// Every variant has a stable, machine-readable code (see [`Error::code`])
+/// that can be used to key alerting or reporting on instead of messages.
+/// The CLI example prints it with `--json-errors`; the crate itself writes no
+/// reports, so serializing the code elsewhere is up to the caller.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Building the HTTP client, sending a request or reading a response failed
+    Http(reqwest::Error),
+    /// The given credentials can not be used in an HTTP header
+    InvalidCredentials(InvalidHeaderValue),
+    /// The operation requires credentials, but none were given
+    CredentialsRequired,
+    /// A config file could not be read
+    ReadConfig {
+        /// The path of the config file
+        path: PathBuf,
+        /// The underlying IO error
+        source: io::Error,
+    },
+    /// A config file does not contain what was expected
+    InvalidConfig {
+        /// The path of the config file
+        path: PathBuf,
+        /// What is wrong with the config file
+        reason: String,
+    },
+    /// The API rejected the credentials
+    AuthFailed,
+    /// Logging in at the target site failed
+    LoginFailed(Box<dyn std::error::Error + Send + Sync>),
+    /// The API rejected the request because too many requests were sent
+    RateLimited {
+        /// How long to wait before sending requests again, if the API said so
+        retry_after: Option<Duration>,
+    },
+    /// The request was not sent because the circuit breaker is open
+    CircuitOpen {
+        /// How long the breaker stays open at least
+        retry_after: Duration,
+    },
+    /// The API responded with an unexpected HTTP status
+    UnexpectedStatus(StatusCode),
+    /// The API response could not be interpreted
+    InvalidResponse(String),
+    /// The API response could not be deserialized
+    Deserialize {
+        /// The underlying deserialization error
+        source: serde_json::Error,
+        /// The raw response body
+        raw: String,
+    },
+    /// The given string is not a valid job ID
+    InvalidJobId(String),
+    /// A capture parameter exceeds its documented limit
+    ParamOutOfLimits(LimitViolation),
+    /// The given string is not a valid capture basis
+    InvalidBasis(String),
+    /// The given byte range is empty or out of bounds
+    InvalidRange(String),
+    /// A capture job was still pending when the waiting deadline passed
+    TimedOut {
+        /// The ID of the capture job
+        job_id: JobId,
+        /// The last observed status of the capture job
+        last_status: Box<SPN2CaptureStatus>,
+    },
+    /// A capture job was still pending when polling it was given up
+    ///
+    /// Either the maximum amount of status polls was reached or the poll
+    /// strategy gave up on the job.
+    PendingLimitReached {
+        /// The ID of the capture job
+        job_id: JobId,
+        /// How often the status was polled
+        polls: u32,
+        /// The time elapsed since the capture was requested
+        elapsed: Duration,
+    },
+    /// Waiting for a capture job was cancelled
+    ///
+    /// The job keeps running, waiting for it can be resumed with
+    /// [`SPN2Client::handle_for`](crate::SPN2Client::handle_for).
+    Cancelled {
+        /// The ID of the capture job
+        job_id: JobId,
+    },
+    /// The system clock is set before the unix epoch
+    Clock(SystemTimeError),
+    /// The operation needs to build a new HTTP client, but the client uses
+    /// one given with [`SPN2ClientBuilder::http_client`](crate::SPN2ClientBuilder::http_client)
+    CustomHttpClient,
+}
+
+impl Error {
+    /// Get the stable machine-readable code of this error
+    ///
+    /// Codes are namespaced with `spn::` and will not change between releases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Http(e) if e.is_timeout() => "spn::timeout",
+            Error::Http(e) if e.is_connect() => "spn::connect_failed",
+            Error::Http(e) if e.is_decode() => "spn::invalid_response",
+            Error::Http(_) => "spn::http",
+            Error::InvalidCredentials(_) => "spn::invalid_credentials",
+            Error::CredentialsRequired => "spn::credentials_required",
+            Error::ReadConfig { .. } => "spn::read_config",
+            Error::InvalidConfig { .. } => "spn::invalid_config",
+            Error::AuthFailed => "spn::auth_failed",
+            Error::LoginFailed(_) => "spn::login_failed",
+            Error::RateLimited { .. } => "spn::rate_limited",
+            Error::CircuitOpen { .. } => "spn::circuit_open",
+            Error::UnexpectedStatus(_) => "spn::unexpected_status",
+            Error::InvalidResponse(_) | Error::Deserialize { .. } => "spn::invalid_response",
+            Error::InvalidJobId(_) => "spn::invalid_job_id",
+            Error::ParamOutOfLimits(_) => "spn::param_out_of_limits",
+            Error::InvalidBasis(_) => "spn::invalid_basis",
+            Error::InvalidRange(_) => "spn::invalid_range",
+            Error::TimedOut { .. } => "spn::wait_timed_out",
+            Error::PendingLimitReached { .. } => "spn::pending_limit_reached",
+            Error::Cancelled { .. } => "spn::cancelled",
+            Error::Clock(_) => "spn::clock",
+            Error::CustomHttpClient => "spn::custom_http_client",
+        }
+    }
+
+    /// Get an actionable suggestion for how to resolve this error, if there is one
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Error::Http(e) if e.is_timeout() => Some("increase the client's timeout"),
+            Error::Http(e) if e.is_connect() => Some("check your network connection"),
+            Error::InvalidCredentials(_) => {
+                Some("make sure the credentials contain no line breaks or other control characters")
+            }
+            Error::CredentialsRequired => {
+                Some("get S3 keys for your account at https://archive.org/account/s3.php")
+            }
+            Error::ReadConfig { .. } | Error::InvalidConfig { .. } => {
+                Some("run `ia configure` to create the config file")
+            }
+            Error::AuthFailed => Some("check your S3 keys at https://archive.org/account/s3.php"),
+            Error::RateLimited { .. } => Some("slow down and retry later"),
+            Error::CircuitOpen { .. } => {
+                Some("the service seems to be down, check the system status")
+            }
+            Error::UnexpectedStatus(s) if s.is_server_error() => {
+                Some("the service may be having issues, check the system status")
+            }
+            Error::InvalidResponse(_) | Error::Deserialize { .. } => {
+                Some("the API may have changed, use the lenient deserialization mode")
+            }
+            Error::InvalidBasis(_) => {
+                Some("use the format <kind>:<reference>, e.g. cited-in:case-123")
+            }
+            Error::TimedOut { .. } => {
+                Some("increase the deadline or check the status of the job later")
+            }
+            Error::PendingLimitReached { .. } => {
+                Some("increase the maximum polls or check the status of the job later")
+            }
+            Error::Clock(_) => Some("check your system clock"),
+            Error::CustomHttpClient => {
+                Some("configure your HTTP client as needed and build a new SPN client with it")
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the class of this error, which determines how it is retried
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Error::Http(e) if e.is_timeout() => ErrorClass::Timeout,
+            Error::Http(e) if e.is_decode() => ErrorClass::InvalidResponse,
+            Error::Http(e) if e.is_builder() => ErrorClass::Other,
+            Error::Http(_) => ErrorClass::Network,
+            Error::AuthFailed => ErrorClass::AuthFailed,
+            Error::RateLimited { .. } => ErrorClass::RateLimited,
+            Error::UnexpectedStatus(s) if s.is_server_error() => ErrorClass::ServerError,
+            Error::UnexpectedStatus(_) => ErrorClass::ClientError,
+            Error::InvalidResponse(_) | Error::Deserialize { .. } => ErrorClass::InvalidResponse,
+            _ => ErrorClass::Other,
+        }
+    }
+
+    pub(crate) fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::AuthFailed,
+            StatusCode::TOO_MANY_REQUESTS => Error::RateLimited { retry_after: None },
+            s => Error::UnexpectedStatus(s),
+        }
+    }
+
+    /// Like [`Error::from_status`], but also reads the Retry-After header
+    pub(crate) fn from_response(resp: &Response) -> Self {
+        match Error::from_status(resp.status()) {
+            Error::RateLimited { .. } => Error::RateLimited {
+                retry_after: resp
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+                    .map(Duration::from_secs),
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "http error: {e}"),
+            Error::InvalidCredentials(_) => write!(f, "credentials are not a valid header value"),
+            Error::CredentialsRequired => write!(f, "credentials are required"),
+            Error::ReadConfig { path, source } => {
+                write!(f, "failed to read config {}: {source}", path.display())
+            }
+            Error::InvalidConfig { path, reason } => {
+                write!(f, "invalid config {}: {reason}", path.display())
+            }
+            Error::AuthFailed => write!(f, "authentication failed"),
+            Error::LoginFailed(e) => write!(f, "login at target site failed: {e}"),
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "rate limited, retry after {retry_after:?}"),
+            Error::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            Error::CircuitOpen { retry_after } => {
+                write!(f, "circuit breaker open for another {retry_after:?}")
+            }
+            Error::UnexpectedStatus(s) => write!(f, "unexpected response status: {s}"),
+            Error::InvalidResponse(msg) => write!(f, "invalid response: {msg}"),
+            Error::Deserialize { source, raw } => write!(f, "invalid response: {source}: {raw}"),
+            Error::InvalidJobId(id) => write!(f, "invalid job id: {id}"),
+            Error::ParamOutOfLimits(violation) => write!(f, "invalid parameter: {violation}"),
+            Error::InvalidBasis(basis) => write!(f, "invalid capture basis: {basis}"),
+            Error::InvalidRange(range) => write!(f, "invalid byte range: {range}"),
+            Error::TimedOut { job_id, .. } => {
+                write!(f, "capture job {job_id} still pending at deadline")
+            }
+            Error::PendingLimitReached {
+                job_id,
+                polls,
+                elapsed,
+            } => write!(
+                f,
+                "capture job {job_id} still pending after {polls} polls in {elapsed:?}"
+            ),
+            Error::Cancelled { job_id } => write!(f, "waiting for capture job {job_id} cancelled"),
+            Error::Clock(e) => write!(f, "invalid system time: {e}"),
+            Error::CustomHttpClient => {
+                write!(f, "a custom HTTP client can not be rebuilt")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            Error::InvalidCredentials(e) => Some(e),
+            Error::ReadConfig { source, .. } => Some(source),
+            Error::LoginFailed(e) => Some(e.as_ref()),
+            Error::Deserialize { source, .. } => Some(source),
+            Error::Clock(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(Error::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.suggestion()
+            .map(|s| Box::new(s) as Box<dyn fmt::Display + 'a>)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<InvalidHeaderValue> for Error {
+    fn from(e: InvalidHeaderValue) -> Self {
+        Error::InvalidCredentials(e)
+    }
+}
+
+impl From<SystemTimeError> for Error {
+    fn from(e: SystemTimeError) -> Self {
+        Error::Clock(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_from_status() {
+        assert_eq!(
+            Error::from_status(StatusCode::UNAUTHORIZED).code(),
+            "spn::auth_failed"
+        );
+        assert_eq!(
+            Error::from_status(StatusCode::TOO_MANY_REQUESTS).code(),
+            "spn::rate_limited"
+        );
+        assert_eq!(
+            Error::from_status(StatusCode::INTERNAL_SERVER_ERROR).code(),
+            "spn::unexpected_status"
+        );
+    }
+}
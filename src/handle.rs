@@ -0,0 +1,146 @@
+use std::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    time::Instant,
+};
+
+use crate::{
+    Error, JobId, SPN2CaptureRequestOptParams, SPN2CaptureResponse, SPN2CaptureStatus, SPN2Client,
+    WaitOptions,
+};
+
+/// A handle to a submitted capture job
+///
+/// Returned by [`SPN2Client::request_capture`], it is bound to the client
+/// that submitted the capture and can be used to track the job.
+///
+/// Awaiting the handle waits until the capture job is finished, using the
+/// default [`WaitOptions`].
+#[derive(Clone)]
+pub struct CaptureHandle<'a> {
+    pub(crate) client: &'a SPN2Client,
+    pub(crate) url: String,
+    pub(crate) opt_params: SPN2CaptureRequestOptParams,
+    pub(crate) response: SPN2CaptureResponse,
+    pub(crate) submitted_at: Instant,
+    pub(crate) duplicate: bool,
+}
+
+impl<'a> CaptureHandle<'a> {
+    pub(crate) fn new(
+        client: &'a SPN2Client,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+        response: SPN2CaptureResponse,
+        duplicate: bool,
+    ) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            opt_params: opt_params.clone(),
+            response,
+            submitted_at: Instant::now(),
+            duplicate,
+        }
+    }
+
+    /// Get the ID of the capture job
+    pub fn job_id(&self) -> &JobId {
+        &self.response.job_id
+    }
+
+    /// Get the URL that was requested to be captured
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Check whether the capture job may have been submitted by this client before
+    ///
+    /// SPN returns the same job ID when a URL is submitted again while it is
+    /// still being captured, so the capture runs only once. This is the case
+    /// if this client got the same job ID within the last hour, or if an
+    /// earlier request for the same URL and parameters got no response, e.g.
+    /// because the connection dropped, and the capture request was retried.
+    pub fn is_duplicate(&self) -> bool {
+        self.duplicate
+    }
+
+    /// Get the API's response to the capture request
+    pub fn response(&self) -> &SPN2CaptureResponse {
+        &self.response
+    }
+
+    /// Consume the handle, returning the API's response to the capture request
+    pub fn into_response(self) -> SPN2CaptureResponse {
+        self.response
+    }
+
+    /// Get the current status of the capture job
+    pub async fn status(&self) -> Result<SPN2CaptureStatus, Error> {
+        self.client.get_capture_status(self.job_id()).await
+    }
+}
+
+impl<'a> IntoFuture for CaptureHandle<'a> {
+    type Output = Result<SPN2CaptureStatus, Error>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let outcome = self.wait(&WaitOptions::default()).await?;
+            Ok(outcome.status)
+        })
+    }
+}
+
+impl SPN2Client {
+    /// Get a handle to a capture job that was submitted earlier
+    ///
+    /// Resumes tracking a job, e.g. after waiting for it failed with
+    /// [`Error::Cancelled`] or across restarts of a program that stored the
+    /// job ID. The job is not submitted again. The parameters are only used
+    /// if the job has to be resubmitted, and deadlines count from now since
+    /// the original submission time is unknown.
+    pub fn handle_for(
+        &self,
+        job_id: JobId,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> CaptureHandle<'_> {
+        let response = SPN2CaptureResponse {
+            url: url.to_string(),
+            job_id,
+            message: None,
+            extra: Default::default(),
+        };
+        CaptureHandle::new(self, url, opt_params, response, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CancellationToken;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn resume_cancelled_wait() {
+        let client = SPN2Client::builder().anonymous().build().unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+        let wait_opts = WaitOptions {
+            cancellation_token: Some(token),
+            ..Default::default()
+        };
+        let params = SPN2CaptureRequestOptParams::default();
+        let handle =
+            client.handle_for("spn2-abc".parse().unwrap(), "https://example.com/", &params);
+        let Err(Error::Cancelled { job_id }) = handle.wait(&wait_opts).await else {
+            panic!("waiting must be cancelled");
+        };
+        let resumed = client.handle_for(job_id, handle.url(), &params);
+        assert_eq!("spn2-abc", resumed.job_id().to_string());
+        assert_eq!("https://example.com/", resumed.url());
+        assert!(!resumed.is_duplicate());
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    collections::HashMap,
+    future::poll_fn,
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+    time::Instant,
+};
+
+use reqwest::Url;
+
+use crate::{SPN2CaptureRequestOptParams, SPN2CaptureResponse};
+
+/// A capture request that was answered by the API
+#[derive(Clone)]
+pub(crate) struct Submission {
+    pub(crate) response: SPN2CaptureResponse,
+    pub(crate) submitted_at: Instant,
+    pub(crate) duplicate: bool,
+}
+
+/// The capture requests a client is currently sending, by normalized URL
+///
+/// Requests for a URL that is already being submitted with the same
+/// parameters wait for that submission instead of sending their own.
+#[derive(Default)]
+pub(crate) struct InFlightCaptures(Mutex<HashMap<String, Arc<Slot>>>);
+
+struct Slot {
+    opt_params: SPN2CaptureRequestOptParams,
+    state: Mutex<SlotState>,
+}
+
+#[derive(Default)]
+struct SlotState {
+    /// `Some(None)` if the submission failed or was cancelled
+    result: Option<Option<Submission>>,
+    wakers: Vec<Waker>,
+}
+
+/// The role of a capture request in coalescing
+pub(crate) enum Coalesced<'a> {
+    /// The request has to be sent, publishing its result through the guard
+    Leader(LeaderGuard<'a>),
+    /// An identical request is in flight, its result can be awaited
+    Follower(Follower),
+}
+
+impl InFlightCaptures {
+    /// Join the in-flight request for the URL or become the one sending it
+    pub(crate) fn join(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Coalesced<'_> {
+        let key = normalize_url(url);
+        let mut slots = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(slot) = slots
+            .get(&key)
+            .filter(|slot| &slot.opt_params == opt_params)
+        {
+            return Coalesced::Follower(Follower(slot.clone()));
+        }
+        let slot = Arc::new(Slot {
+            opt_params: opt_params.clone(),
+            state: Mutex::default(),
+        });
+        // a request with other parameters replaces the slot for later requests,
+        // the leader of the old slot still publishes to its own followers
+        slots.insert(key.clone(), slot.clone());
+        Coalesced::Leader(LeaderGuard {
+            captures: self,
+            key,
+            slot,
+        })
+    }
+}
+
+/// Publishes the result of a sent capture request to the waiting requests
+///
+/// Dropping the guard without finishing, e.g. because the request was
+/// cancelled, lets the waiting requests send their own.
+pub(crate) struct LeaderGuard<'a> {
+    captures: &'a InFlightCaptures,
+    key: String,
+    slot: Arc<Slot>,
+}
+
+impl LeaderGuard<'_> {
+    /// Publish a successful submission
+    pub(crate) fn finish(self, submission: &Submission) {
+        self.publish(Some(submission.clone()));
+    }
+
+    fn publish(&self, result: Option<Submission>) {
+        {
+            let mut slots = self.captures.0.lock().unwrap_or_else(|e| e.into_inner());
+            if slots
+                .get(&self.key)
+                .is_some_and(|slot| Arc::ptr_eq(slot, &self.slot))
+            {
+                slots.remove(&self.key);
+            }
+        }
+        let mut state = self.slot.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.result.is_none() {
+            state.result = Some(result);
+            state.wakers.drain(..).for_each(Waker::wake);
+        }
+    }
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        self.publish(None);
+    }
+}
+
+/// A capture request waiting for an identical request in flight
+pub(crate) struct Follower(Arc<Slot>);
+
+impl Follower {
+    /// Wait for the in-flight request, getting `None` if it failed
+    pub(crate) async fn wait(self) -> Option<Submission> {
+        poll_fn(|cx| {
+            let mut state = self.0.state.lock().unwrap_or_else(|e| e.into_inner());
+            match &state.result {
+                Some(result) => Poll::Ready(result.clone()),
+                None => {
+                    // a follower is polled repeatedly, its waker is only kept once
+                    if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                        state.wakers.push(cx.waker().clone());
+                    }
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+}
+
+/// Normalize a URL so that different spellings of the same URL match
+///
+/// The scheme and host are lowercased, default ports and fragments dropped.
+/// URLs that cannot be parsed are used as is.
+pub(crate) fn normalize_url(url: &str) -> String {
+    match Url::parse(url.trim()) {
+        Ok(mut url) => {
+            url.set_fragment(None);
+            url.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission() -> Submission {
+        Submission {
+            response: serde_json::from_str(r#"{"url":"https://example.com","job_id":"spn2-abc"}"#)
+                .unwrap(),
+            submitted_at: Instant::now(),
+            duplicate: false,
+        }
+    }
+
+    #[test]
+    fn normalize_urls() {
+        assert_eq!(
+            normalize_url("HTTPS://Example.com:443/a?b#c"),
+            normalize_url("https://example.com/a?b")
+        );
+        assert_ne!(
+            normalize_url("https://example.com/a"),
+            normalize_url("https://example.com/b")
+        );
+    }
+
+    #[tokio::test]
+    async fn coalesce_identical_requests() {
+        let captures = InFlightCaptures::default();
+        let params = SPN2CaptureRequestOptParams::default();
+        let Coalesced::Leader(leader) = captures.join("https://example.com", &params) else {
+            panic!("first request must lead");
+        };
+        let Coalesced::Follower(follower) = captures.join("https://EXAMPLE.com/", &params) else {
+            panic!("identical request must follow");
+        };
+        leader.finish(&submission());
+        let joined = follower.wait().await.unwrap();
+        assert_eq!("spn2-abc", joined.response.job_id.to_string());
+        assert!(matches!(
+            captures.join("https://example.com", &params),
+            Coalesced::Leader(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_leader_releases_followers() {
+        let captures = InFlightCaptures::default();
+        let params = SPN2CaptureRequestOptParams::default();
+        let leader = captures.join("https://example.com", &params);
+        let Coalesced::Follower(follower) = captures.join("https://example.com", &params) else {
+            panic!("identical request must follow");
+        };
+        drop(leader);
+        assert!(follower.wait().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn register_waker_once() {
+        let captures = InFlightCaptures::default();
+        let params = SPN2CaptureRequestOptParams::default();
+        let Coalesced::Leader(leader) = captures.join("https://example.com", &params) else {
+            panic!("first request must lead");
+        };
+        let Coalesced::Follower(follower) = captures.join("https://example.com", &params) else {
+            panic!("identical request must follow");
+        };
+        let slot = follower.0.clone();
+        let mut wait = std::pin::pin!(follower.wait());
+        for _ in 0..3 {
+            assert!(poll_once(wait.as_mut()).await.is_none());
+        }
+        assert_eq!(1, slot.state.lock().unwrap().wakers.len());
+        leader.finish(&submission());
+        assert!(wait.await.is_some());
+    }
+
+    /// Poll a future once, returning its output if it is ready
+    async fn poll_once<F: std::future::Future + Unpin>(mut fut: F) -> Option<F::Output> {
+        poll_fn(|cx| {
+            Poll::Ready(match std::pin::Pin::new(&mut fut).poll(cx) {
+                Poll::Ready(output) => Some(output),
+                Poll::Pending => None,
+            })
+        })
+        .await
+    }
+}
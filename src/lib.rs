@@ -10,21 +10,106 @@
 //! API reference:
 //! <https://docs.google.com/document/d/1Nsv52MvSjbLb2PCpHlat0gkzw0EvtSgpKHu4mk0MnrA>
 
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDateTime, Utc};
 
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Client, ClientBuilder, StatusCode,
+    header::{HeaderMap, AUTHORIZATION, CONTENT_LOCATION},
+    Client, IntoUrl, Method, Proxy, RequestBuilder, StatusCode, Url,
 };
-use serde::{Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+mod api;
+mod archive;
+mod backoff;
+mod basis;
+mod builder;
+mod challenge;
+mod circuit;
+mod compare;
+mod config;
+mod credentials;
+mod de;
+mod error;
+mod handle;
+mod inflight;
+pub mod limits;
+mod login;
+pub mod messages;
+mod outcome;
+mod pause;
+mod playback;
+mod proxy;
+mod raw;
+mod recording;
+mod retry;
+mod sessions;
+mod snapshot;
+mod submitted;
+mod timeline;
+mod wait;
 
-/// Errors that may occur when constructing the client and sending requests
-pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+pub use api::SpnApi;
+pub use archive::{archive, archive_all, ArchiveOptions, BatchSummary, RetryBudget};
+pub use backoff::{
+    Backoff, BackoffConfig, DecorrelatedJitterBackoff, ExponentialBackoff, FibonacciBackoff,
+    FixedBackoff, ScheduleBackoff,
+};
+pub use basis::CaptureBasis;
+use builder::HttpSettings;
+pub use builder::{HttpVersion, IpVersion, SPN2ClientBuilder, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT};
+pub use challenge::BotChallenge;
+pub use circuit::CircuitBreaker;
+pub use compare::{LiveComparison, DEFAULT_MIN_SIMILARITY};
+pub use config::EffectiveConfig;
+use credentials::Auth;
+pub use credentials::Credentials;
+pub use de::DeserializeMode;
+pub use error::Error;
+pub use handle::CaptureHandle;
+use inflight::{Coalesced, InFlightCaptures, Submission};
+use limits::LimitMode;
+use messages::KnownMessage;
+pub use outcome::{CaptureError, CaptureOutcome, Resubmission};
+use pause::RateLimitPause;
+pub use playback::{wayback_url, PlaybackMode};
+pub use proxy::ProxyConfig;
+pub use recording::{RecordedRequest, RecordingClient};
+use retry::SystemStatusPause;
+pub use retry::{ErrorClass, RetryPolicy, RetryRule, StandardRetryPolicy};
+pub use secrecy::SecretString;
+use sessions::SessionLimiter;
+pub use snapshot::{Snapshot, SnapshotInfo};
+use submitted::SubmittedJobs;
+use timeline::Timelines;
+pub use timeline::{TimelineEntry, TimelineEvent};
+pub use tokio_util::sync::CancellationToken;
+pub use wait::{
+    PollState, PollStrategy, Progress, ProgressCallback, StalePendingPolicy, WaitOptions,
+    DEFAULT_POLL_INTERVAL,
+};
 
 const API_CAPTURE_URL: &str = "https://web.archive.org/save";
 const API_CAPTURE_STATUS_URL: &str = "https://web.archive.org/save/status";
 const API_USER_STATUS_URL: &str = "https://web.archive.org/save/status/user";
 const API_SYSTEM_STATUS_URL: &str = "https://web.archive.org/save/status/system";
+const WAYBACK_URL: &str = "https://web.archive.org/web";
+
+/// The `status_ext` codes of capture errors that usually resolve within minutes
+const TRANSIENT_STATUS_EXTS: &[&str] = &[
+    "error:no-browsers-available",
+    "error:celery",
+    "error:soft-time-limit-exceeded",
+];
 
 /// Parameters for a capture request
 ///
@@ -47,7 +132,7 @@ const API_SYSTEM_STATUS_URL: &str = "https://web.archive.org/save/status/system"
 /// };
 /// ```
 #[allow(missing_docs)]
-#[derive(Default, Serialize)]
+#[derive(Default, Clone, PartialEq, Serialize)]
 pub struct SPN2CaptureRequestOptParams {
     #[serde(serialize_with = "serialize_bool_param")]
     pub capture_all: bool,
@@ -79,12 +164,126 @@ pub struct SPN2CaptureRequestOptParams {
     pub target_username: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_password: Option<String>,
+    /// Why the page is archived, kept with the capture but not sent to the API
+    #[serde(skip)]
+    pub basis: Option<CaptureBasis>,
+    /// Experimental or undocumented parameters
+    ///
+    /// **Unstable**: set with `experimental_param`, which is available with
+    /// the `experimental-params` feature, may change or be removed in any
+    /// release. The field exists in every build so that enabling the feature
+    /// does not break struct literals.
+    #[doc(hidden)]
+    #[serde(flatten)]
+    pub experimental: std::collections::BTreeMap<String, String>,
+}
+
+impl fmt::Debug for SPN2CaptureRequestOptParams {
+    /// Formats the parameters with the cookies and the target password redacted
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = self.redacted();
+        f.debug_struct("SPN2CaptureRequestOptParams")
+            .field("capture_all", &redacted.capture_all)
+            .field("capture_outlinks", &redacted.capture_outlinks)
+            .field("capture_screenshot", &redacted.capture_screenshot)
+            .field("delay_wb_availability", &redacted.delay_wb_availability)
+            .field("force_get", &redacted.force_get)
+            .field("skip_first_archive", &redacted.skip_first_archive)
+            .field("outlinks_availability", &redacted.outlinks_availability)
+            .field("email_result", &redacted.email_result)
+            .field("if_not_archived_within", &redacted.if_not_archived_within)
+            .field("js_behavior_timeout", &redacted.js_behavior_timeout)
+            .field("capture_cookie", &redacted.capture_cookie)
+            .field("use_user_agent", &redacted.use_user_agent)
+            .field("target_username", &redacted.target_username)
+            .field("target_password", &redacted.target_password)
+            .field("basis", &redacted.basis)
+            .field("experimental", &redacted.experimental)
+            .finish()
+    }
+}
+
+impl SPN2CaptureRequestOptParams {
+    /// Get a copy with the cookies and the target password replaced by `***`
+    pub(crate) fn redacted(&self) -> Self {
+        let redact = |secret: &Option<String>| secret.as_ref().map(|_| "***".to_string());
+        Self {
+            capture_cookie: redact(&self.capture_cookie),
+            target_password: redact(&self.target_password),
+            ..self.clone()
+        }
+    }
+
+    /// Add a cookie to send with the capture
+    ///
+    /// Cookies are appended to [`capture_cookie`](Self::capture_cookie).
+    pub fn with_cookie(mut self, name: &str, value: &str) -> Self {
+        let cookie = format!("{name}={value}");
+        self.capture_cookie = Some(match self.capture_cookie.take() {
+            Some(cookies) if !cookies.is_empty() => format!("{cookies}; {cookie}"),
+            _ => cookie,
+        });
+        self
+    }
+
+    /// Set why the page is archived, see [`CaptureBasis`]
+    pub fn with_basis(mut self, basis: CaptureBasis) -> Self {
+        self.basis = Some(basis);
+        self
+    }
+
+    /// Hint the captured site to render in the given language, e.g `de-DE`
+    ///
+    /// SPN2 does not support setting an `Accept-Language` header, so this
+    /// sends the commonly used `lang` cookie instead. Sites using another
+    /// cookie need to be given it with [`with_cookie`](Self::with_cookie).
+    pub fn locale(self, lang_tag: &str) -> Self {
+        self.with_cookie("lang", lang_tag)
+    }
+}
+
+#[cfg(feature = "experimental-params")]
+impl SPN2CaptureRequestOptParams {
+    /// Set an experimental or undocumented parameter
+    ///
+    /// **Unstable**: available with the `experimental-params` feature, may
+    /// change or be removed in any release.
+    pub fn experimental_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.experimental.insert(name.into(), value.into());
+        self
+    }
 }
 
 /// The client for the SPN2 API
+///
+/// Use [`SPN2Client::builder`] for more configuration options than
+/// [`SPN2Client::new`] offers.
 pub struct SPN2Client {
     http_client: Client,
+    auth: Arc<RwLock<Option<Auth>>>,
+    headers: HeaderMap,
     timeout: Duration,
+    http_settings: HttpSettings,
+    retry_policy: Arc<dyn RetryPolicy>,
+    system_status_pause: Option<SystemStatusPause>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    rate_limit_pause: Arc<RateLimitPause>,
+    limit_mode: LimitMode,
+    deserialize_mode: DeserializeMode,
+    timelines: Option<Arc<Timelines>>,
+    submitted: Arc<SubmittedJobs>,
+    in_flight: Option<Arc<InFlightCaptures>>,
+    sessions: Option<Arc<SessionLimiter>>,
+    default_capture_params: SPN2CaptureRequestOptParams,
+}
+
+impl fmt::Debug for SPN2Client {
+    /// Print the effective configuration, which contains no secrets
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SPN2Client")
+            .field(&self.effective_config())
+            .finish()
+    }
 }
 
 impl SPN2Client {
@@ -94,44 +293,273 @@ impl SPN2Client {
         api_secret: String,
         timeout: Duration,
     ) -> Result<Self, Error> {
-        let mut headers = HeaderMap::new();
-        let mut auth_value = HeaderValue::from_str(&format!("LOW {api_access_key}:{api_secret}"))?;
-        auth_value.set_sensitive(true);
-        headers.insert("Authorization", auth_value);
-        headers.insert("Accept", HeaderValue::from_static("application/json"));
-        let http_client = ClientBuilder::new().default_headers(headers).build()?;
-        Ok(Self {
+        Self::builder()
+            .with_credentials(Credentials::new(api_access_key, api_secret))
+            .timeout(timeout)
+            .build()
+    }
+
+    /// Create a new client that sends its requests with the given HTTP client
+    ///
+    /// This allows sharing a client, and its connection pool, proxy and TLS
+    /// settings, with the rest of an application.
+    pub fn with_http_client(
+        http_client: Client,
+        credentials: Credentials,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        Self::builder()
+            .http_client(http_client)
+            .with_credentials(credentials)
+            .timeout(timeout)
+            .build()
+    }
+
+    /// Create a new client without credentials
+    ///
+    /// Only [`SPN2Client::get_system_status`] and [`SPN2Client::legacy_capture`]
+    /// work without credentials, the other operations fail with
+    /// [`Error::CredentialsRequired`].
+    pub fn anonymous() -> Result<Self, Error> {
+        Self::builder().anonymous().build()
+    }
+
+    /// Create a builder to configure a new client
+    pub fn builder() -> SPN2ClientBuilder {
+        SPN2ClientBuilder::new()
+    }
+
+    /// Create a client that sends its requests through the given proxy
+    ///
+    /// The new client shares the credentials, including later rotations,
+    /// settings and recorded timelines of this client. Only the HTTP client is
+    /// replaced by a new one that uses the proxy. This allows switching the
+    /// proxy for some requests, e.g. when an exit node got blocked, without
+    /// reconfiguring everything else.
+    ///
+    /// Fails with [`Error::CustomHttpClient`] if the client uses an HTTP
+    /// client given with [`SPN2ClientBuilder::http_client`], since its
+    /// settings can not be carried over to a new one.
+    pub fn with_proxy(&self, proxy: Proxy) -> Result<SPN2Client, Error> {
+        if self.http_settings.custom_client {
+            return Err(Error::CustomHttpClient);
+        }
+        let described = format!("{proxy:?}");
+        let http_client = self.http_settings.client_builder().proxy(proxy).build()?;
+        let mut client = self.sharing(http_client, self.retry_policy.clone());
+        client.http_settings.proxies = vec![described];
+        Ok(client)
+    }
+
+    /// Create a client that retries failed requests with the given policy
+    ///
+    /// Shares everything else with this client, like [`Self::with_proxy`].
+    pub(crate) fn with_retry_policy(&self, retry_policy: Arc<dyn RetryPolicy>) -> SPN2Client {
+        self.sharing(self.http_client.clone(), retry_policy)
+    }
+
+    /// Create a client sharing the state and settings of this one
+    fn sharing(&self, http_client: Client, retry_policy: Arc<dyn RetryPolicy>) -> SPN2Client {
+        SPN2Client {
             http_client,
-            timeout,
-        })
+            auth: self.auth.clone(),
+            headers: self.headers.clone(),
+            timeout: self.timeout,
+            http_settings: self.http_settings.clone(),
+            retry_policy,
+            system_status_pause: self.system_status_pause.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            rate_limit_pause: self.rate_limit_pause.clone(),
+            limit_mode: self.limit_mode,
+            deserialize_mode: self.deserialize_mode,
+            timelines: self.timelines.clone(),
+            submitted: self.submitted.clone(),
+            in_flight: self.in_flight.clone(),
+            sessions: self.sessions.clone(),
+            default_capture_params: self.default_capture_params.clone(),
+        }
+    }
+
+    /// Replace the credentials used for all further requests
+    ///
+    /// Requests that are already in flight keep the previous credentials. The
+    /// HTTP client and its connection pool are kept, so this is cheap enough to
+    /// call whenever the keys are rotated.
+    pub fn set_credentials(
+        &self,
+        api_access_key: &str,
+        api_secret: impl Into<SecretString>,
+    ) -> Result<(), Error> {
+        let auth = Auth::try_from(&Credentials::new(api_access_key, api_secret))?;
+        *self.auth.write().expect("credentials lock poisoned") = Some(auth);
+        Ok(())
+    }
+
+    fn auth(&self) -> RwLockReadGuard<'_, Option<Auth>> {
+        self.auth.read().expect("credentials lock poisoned")
+    }
+
+    /// Check whether the client has no credentials
+    pub fn is_anonymous(&self) -> bool {
+        self.auth().is_none()
+    }
+
+    /// Create a request to the SPN API with headers and timeout set
+    ///
+    /// The authorization is set if the client has credentials.
+    fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        let req = self
+            .http_client
+            .request(method, url)
+            .headers(self.headers.clone())
+            .timeout(self.timeout);
+        match self.auth().as_ref() {
+            Some(auth) => req.header(AUTHORIZATION, auth.header.clone()),
+            None => req,
+        }
+    }
+
+    /// Create a request to the SPN API that requires credentials
+    ///
+    /// Fails with [`Error::CredentialsRequired`] if the client is anonymous.
+    fn authed_request(&self, method: Method, url: impl IntoUrl) -> Result<RequestBuilder, Error> {
+        if self.is_anonymous() {
+            return Err(Error::CredentialsRequired);
+        }
+        Ok(self.request(method, url))
     }
 
     /// Set the timeout for requests to the SPN API
     pub fn set_timeout(&mut self, timeout: Duration) {
         self.timeout = timeout;
     }
+
+    /// Set how strictly API responses are deserialized
+    pub fn set_deserialize_mode(&mut self, mode: DeserializeMode) {
+        self.deserialize_mode = mode;
+    }
+
+    /// Set whether the timelines of capture jobs are recorded
+    ///
+    /// When enabled, every observed change of a job's state is recorded in
+    /// memory until it is removed with [`SPN2Client::take_job_timeline`], or
+    /// an hour after the job's last recorded change.
+    /// Disabling the recording discards all recorded timelines.
+    pub fn set_record_timelines(&mut self, record: bool) {
+        self.timelines = record.then(|| Arc::new(Timelines::default()));
+    }
+
+    /// Get the recorded timeline of a capture job
+    ///
+    /// The timeline is empty if recording is disabled or the job is unknown.
+    pub fn job_timeline(&self, job_id: &JobId) -> Vec<TimelineEntry> {
+        self.timelines
+            .as_ref()
+            .map(|t| t.get(job_id))
+            .unwrap_or_default()
+    }
+
+    /// Remove the recorded timeline of a capture job, returning it
+    pub fn take_job_timeline(&self, job_id: &JobId) -> Vec<TimelineEntry> {
+        self.timelines
+            .as_ref()
+            .map(|t| t.remove(job_id))
+            .unwrap_or_default()
+    }
 }
 
 /// The SPN2 API's response to a capture request
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SPN2CaptureResponse {
     /// The requested URL to capture
     pub url: String,
     /// The ID of the capture request  
     /// Use this to issue status requests
-    pub job_id: String,
+    pub job_id: JobId,
+    /// An optional message, e.g about a reused recent snapshot
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Fields of the response that are not modeled
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl SPN2CaptureResponse {
+    /// Interpret the response's message, if it is a known one
+    pub fn known_message(&self) -> Option<KnownMessage> {
+        self.message
+            .as_deref()
+            .and_then(messages::interpret_message)
+    }
+}
+
+/// The ID of a capture job
+///
+/// Deserialized IDs are validated like parsed ones, see [`JobId::from_str`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct JobId(String);
+
+impl JobId {
+    /// Get the ID as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for JobId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |c: char| c.is_whitespace() || matches!(c, '/' | '?' | '#');
+        if s.is_empty() || s.contains(invalid) {
+            return Err(Error::InvalidJobId(s.to_string()));
+        }
+        Ok(JobId(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for JobId {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<JobId> for String {
+    fn from(job_id: JobId) -> Self {
+        job_id.0
+    }
+}
+
+impl AsRef<str> for JobId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
 
 /// The SPN2 API's response to a capture status request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(tag = "status")]
+#[non_exhaustive]
 pub enum SPN2CaptureStatus {
     /// Status: Pending  
     /// Capture request has not been fully processed.
     #[serde(rename = "pending")]
     Pending {
         /// List of captured resources
+        #[serde(default)]
         resources: Vec<String>,
+        /// Fields of the response that are not modeled
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
     /// Status: Error  
     /// Capture request was not successful, some error occured.
@@ -144,7 +572,11 @@ pub enum SPN2CaptureStatus {
         /// The error message
         message: String,
         /// List of captured resources
+        #[serde(default)]
         resources: Vec<String>,
+        /// Fields of the response that are not modeled
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
     /// Status: Success  
     /// Capture request was successfully processed.
@@ -157,25 +589,151 @@ pub enum SPN2CaptureStatus {
         /// Timestamp in YYYYMMDDHHMMSS format
         timestamp: String,
         /// Duration of capture processing
-        duration_sec: f64,
+        #[serde(
+            deserialize_with = "deserialize_duration_secs",
+            serialize_with = "serialize_duration_secs"
+        )]
+        duration_sec: Duration,
         /// List of captured resources
+        #[serde(default)]
         resources: Vec<String>,
-        /// List of links to other sites
-        outlinks: Vec<String>,
+        /// Links to other sites
+        #[serde(default)]
+        outlinks: Outlinks,
+        /// Counts of what the capture pulled in
+        #[serde(default)]
+        counters: Option<CaptureCounters>,
+        /// HTTP status code of the captured page
+        #[serde(default)]
+        http_status: Option<TargetStatus>,
+        /// Whether this is the first archive of the URL
+        #[serde(default)]
+        first_archive: bool,
+        /// Fields of the response that are not modeled
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
 }
 
+/// The links to other sites of a successful capture
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum Outlinks {
+    /// Plain list of links
+    List(Vec<String>),
+    /// Links mapped to the timestamp of their latest snapshot, if any
+    ///
+    /// Returned when the `outlinks_availability` parameter is set.
+    Availability(HashMap<String, Option<String>>),
+}
+
+impl Default for Outlinks {
+    fn default() -> Self {
+        Outlinks::List(Vec::new())
+    }
+}
+
+impl Outlinks {
+    /// Iterate over the links
+    pub fn urls(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self {
+            Outlinks::List(urls) => Box::new(urls.iter().map(String::as_str)),
+            Outlinks::Availability(urls) => Box::new(urls.keys().map(String::as_str)),
+        }
+    }
+
+    /// Get the number of links
+    pub fn len(&self) -> usize {
+        match self {
+            Outlinks::List(urls) => urls.len(),
+            Outlinks::Availability(urls) => urls.len(),
+        }
+    }
+
+    /// Check whether there are no links
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Counts of what a successful capture pulled in
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct CaptureCounters {
+    /// Number of links to other pages
+    #[serde(default)]
+    pub outlinks: u64,
+    /// Number of embedded resources
+    #[serde(default)]
+    pub embeds: u64,
+    /// Counters that are not modeled
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl CaptureCounters {
+    /// Get the sum of all counters, including numeric counters that are not modeled
+    pub fn total(&self) -> u64 {
+        let extra: u64 = self.extra.values().filter_map(Value::as_u64).sum();
+        self.outlinks + self.embeds + extra
+    }
+}
+
+/// The HTTP status code the captured page was served with
+///
+/// Nonstandard codes are kept as is, see [`TargetStatus::status_code`].
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct TargetStatus(u16);
+
+impl TargetStatus {
+    /// Get the raw status code
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Get the typed status code, if the raw code is a valid HTTP status code
+    pub fn status_code(&self) -> Option<StatusCode> {
+        StatusCode::from_u16(self.0).ok()
+    }
+
+    /// Check whether the page was served with a client or server error status
+    pub fn is_error(&self) -> bool {
+        self.0 >= 400
+    }
+}
+
+impl From<u16> for TargetStatus {
+    fn from(code: u16) -> Self {
+        Self(code)
+    }
+}
+
+impl fmt::Display for TargetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status_code() {
+            Some(status_code) => write!(f, "{status_code}"),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
 /// The SPN2 API's response to a user status request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SPN2UserStatus {
     /// The user's amount of available sessions
     pub available: usize,
     /// The user's amount of active sessions
     pub processing: usize,
+    /// Fields of the response that are not modeled
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// The SPN2 API's response to a system status request
-#[derive(Debug)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum SPN2SystemStatus {
     /// Everything is fine
     Ok,
@@ -191,79 +749,445 @@ pub enum SPN2SystemStatus {
 
 impl SPN2Client {
     /// Issue a capture request for the given URL
+    ///
+    /// The returned handle can be used to track the capture job.
     pub async fn request_capture(
         &self,
         url: &str,
         opt_params: &SPN2CaptureRequestOptParams,
-    ) -> Result<SPN2CaptureResponse, Error> {
-        let params = SPN2CaptureRequestParams { url, opt_params };
-        let req = self
-            .http_client
-            .post(API_CAPTURE_URL)
-            .timeout(self.timeout)
-            .form(&params);
-        eprintln!("{req:?}");
-        let resp = req.send().await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json::<SPN2CaptureResponse>().await?),
-            s => Err(format!("unexpected response status: {s}").into()),
+    ) -> Result<CaptureHandle<'_>, Error> {
+        let leader = match self.in_flight.as_ref().map(|c| c.join(url, opt_params)) {
+            Some(Coalesced::Leader(leader)) => Some(leader),
+            Some(Coalesced::Follower(follower)) => match follower.wait().await {
+                Some(submission) => {
+                    let mut handle = CaptureHandle::new(
+                        self,
+                        url,
+                        opt_params,
+                        submission.response,
+                        submission.duplicate,
+                    );
+                    handle.submitted_at = submission.submitted_at;
+                    return Ok(handle);
+                }
+                // the identical request failed, so this one is sent on its own
+                None => None,
+            },
+            None => None,
+        };
+        if let Some(sessions) = &self.sessions {
+            sessions.acquire(self).await?;
+        }
+        let handle = self
+            .retrying(|| async {
+                let req = self.capture_request(url, opt_params)?;
+                let body = match req.send().await {
+                    Ok(resp) if resp.status() == StatusCode::OK => resp.text().await,
+                    Ok(resp) => return Err(Error::from_response(&resp)),
+                    Err(e) => Err(e),
+                };
+                match body {
+                    Ok(body) => {
+                        let resp: SPN2CaptureResponse = de::from_body(body, self.deserialize_mode)?;
+                        let seen = self.submitted.record(&resp.job_id);
+                        let unanswered = self.submitted.take_unanswered(url, opt_params);
+                        if let Some(timelines) = self.timelines.as_ref().filter(|_| !seen) {
+                            timelines.record_submitted(&resp.job_id);
+                        }
+                        let duplicate = seen || unanswered;
+                        Ok(CaptureHandle::new(self, url, opt_params, resp, duplicate))
+                    }
+                    // the request may have reached SPN unless the connection failed
+                    Err(e) => {
+                        if !e.is_connect() {
+                            self.submitted.record_unanswered(url, opt_params);
+                        }
+                        Err(e.into())
+                    }
+                }
+            })
+            .await?;
+        if let Some(leader) = leader {
+            leader.finish(&Submission {
+                response: handle.response.clone(),
+                submitted_at: handle.submitted_at,
+                duplicate: handle.duplicate,
+            });
         }
+        Ok(handle)
+    }
+
+    /// Issue a capture request for the given URL using the default capture parameters
+    ///
+    /// The default capture parameters are set with
+    /// [`SPN2ClientBuilder::default_capture_params`].
+    pub async fn request_capture_with_defaults(
+        &self,
+        url: &str,
+    ) -> Result<CaptureHandle<'_>, Error> {
+        self.request_capture(url, &self.default_capture_params)
+            .await
     }
 
     /// Get the current status of a capture job
-    pub async fn get_capture_status(&self, job_id: &str) -> Result<SPN2CaptureStatus, Error> {
-        let resp = self
-            .http_client
-            .get(format!("{API_CAPTURE_STATUS_URL}/{job_id}"))
-            .timeout(self.timeout)
-            .send()
-            .await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json::<SPN2CaptureStatus>().await?),
-            s => Err(format!("unexpected response status: {s}").into()),
-        }
+    pub async fn get_capture_status(&self, job_id: &JobId) -> Result<SPN2CaptureStatus, Error> {
+        self.retrying(|| async {
+            let resp = self.capture_status_request(job_id)?.send().await?;
+            match resp.status() {
+                StatusCode::OK => {
+                    let status = de::from_body(resp.text().await?, self.deserialize_mode)?;
+                    if let Some(timelines) = &self.timelines {
+                        timelines.record_status(job_id, &status);
+                    }
+                    Ok(status)
+                }
+                _ => Err(Error::from_response(&resp)),
+            }
+        })
+        .await
     }
 
     /// Get the current status of the user
     pub async fn get_user_status(&self) -> Result<SPN2UserStatus, Error> {
-        let unix_secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        let params = [("_t", unix_secs)];
+        self.retrying(|| async {
+            let resp = self.user_status_request()?.send().await?;
+            match resp.status() {
+                StatusCode::OK => de::from_body(resp.text().await?, self.deserialize_mode),
+                _ => Err(Error::from_response(&resp)),
+            }
+        })
+        .await
+    }
+
+    /// Check whether the credentials are accepted by the API
+    ///
+    /// Cheap enough to run at startup, before submitting a big batch of
+    /// captures. Rejected credentials result in [`CredentialsCheck::Invalid`],
+    /// other failures, e.g. network errors, are returned as errors.
+    pub async fn validate_credentials(&self) -> Result<CredentialsCheck, Error> {
+        let resp = self.user_status_request()?.send().await?;
+        match resp.status() {
+            StatusCode::OK => {
+                let body = resp.text().await?;
+                match CredentialsCheck::from_error_body(&body) {
+                    Some(check) => Ok(check),
+                    None => de::from_body(body, self.deserialize_mode).map(CredentialsCheck::Valid),
+                }
+            }
+            s => match Error::from_status(s) {
+                Error::AuthFailed => Ok(CredentialsCheck::Invalid {
+                    reason: format!("authentication failed with status {s}"),
+                }),
+                e => Err(e),
+            },
+        }
+    }
+
+    /// Capture the given URL with the legacy `GET /save/{url}` endpoint
+    ///
+    /// Works without credentials, but does not support capture parameters and
+    /// blocks until the capture is done. Returns the URL of the snapshot in the
+    /// Wayback Machine.
+    pub async fn legacy_capture(&self, url: &str) -> Result<String, Error> {
         let resp = self
-            .http_client
-            .get(API_USER_STATUS_URL)
-            .query(&params)
-            .timeout(self.timeout)
+            .request(Method::GET, format!("{API_CAPTURE_URL}/{url}"))
             .send()
             .await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json::<SPN2UserStatus>().await?),
-            s => Err(format!("unexpected response status: {s}").into()),
+        if !resp.status().is_success() {
+            return Err(Error::from_response(&resp));
         }
+        let content_location = resp
+            .headers()
+            .get(CONTENT_LOCATION)
+            .and_then(|value| value.to_str().ok());
+        legacy_snapshot_url(content_location, resp.url()).ok_or_else(|| {
+            Error::InvalidResponse(format!("no snapshot location in response for {url}"))
+        })
     }
 
     /// Get the current status of the SPN system
     pub async fn get_system_status(&self) -> Result<SPN2SystemStatus, Error> {
-        let resp = self
-            .http_client
-            .get(API_SYSTEM_STATUS_URL)
-            .timeout(self.timeout)
-            .send()
-            .await?;
-        match resp.status() {
-            StatusCode::OK => SPN2SystemStatus::from_json(resp.json::<serde_json::Value>().await?),
-            StatusCode::BAD_GATEWAY => Ok(SPN2SystemStatus::Critical),
-            s => Err(format!("unexpected response status: {s}").into()),
+        self.retrying(|| self.fetch_system_status()).await
+    }
+
+    /// Get the current status of the SPN system without retrying
+    pub(crate) async fn fetch_system_status(&self) -> Result<SPN2SystemStatus, Error> {
+        let resp = self.system_status_request().send().await?;
+        let status = match resp.status() {
+            StatusCode::OK => SPN2SystemStatus::from_json(resp.json::<Value>().await?)?,
+            StatusCode::BAD_GATEWAY => SPN2SystemStatus::Critical,
+            _ => return Err(Error::from_response(&resp)),
+        };
+        if let Some(breaker) = self.circuit_breaker.as_ref() {
+            if status == SPN2SystemStatus::Critical {
+                breaker.trip();
+            }
+        }
+        Ok(status)
+    }
+
+    /// Build a capture request for the given URL
+    pub(crate) fn capture_request(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Result<RequestBuilder, Error> {
+        let opt_params = &*opt_params
+            .within_limits(self.limit_mode)
+            .map_err(Error::ParamOutOfLimits)?;
+        let params = SPN2CaptureRequestParams { url, opt_params };
+        Ok(self
+            .authed_request(Method::POST, API_CAPTURE_URL)?
+            .form(&params))
+    }
+
+    /// Build a capture status request for the given job
+    pub(crate) fn capture_status_request(&self, job_id: &JobId) -> Result<RequestBuilder, Error> {
+        self.authed_request(Method::GET, format!("{API_CAPTURE_STATUS_URL}/{job_id}"))
+    }
+
+    /// Build a user status request
+    pub(crate) fn user_status_request(&self) -> Result<RequestBuilder, Error> {
+        let unix_secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let params = [("_t", unix_secs)];
+        Ok(self
+            .authed_request(Method::GET, API_USER_STATUS_URL)?
+            .query(&params))
+    }
+
+    /// Build a system status request
+    pub(crate) fn system_status_request(&self) -> RequestBuilder {
+        self.request(Method::GET, API_SYSTEM_STATUS_URL)
+    }
+}
+
+impl SPN2CaptureStatus {
+    /// Interpret the `status_ext` code and message of an error status, if known
+    pub fn known_message(&self) -> Option<KnownMessage> {
+        match self {
+            SPN2CaptureStatus::Error {
+                status_ext,
+                message,
+                ..
+            } => messages::interpret(Some(status_ext), Some(message)),
+            _ => None,
+        }
+    }
+
+    /// Check whether this is an error that usually resolves within minutes
+    ///
+    /// Resubmitting such a capture is likely to succeed, see
+    /// [`WaitOptions::transient_resubmits`].
+    pub fn is_transient_error(&self) -> bool {
+        matches!(
+            self,
+            SPN2CaptureStatus::Error { status_ext, .. }
+                if TRANSIENT_STATUS_EXTS.contains(&status_ext.as_str())
+        )
+    }
+
+    /// Get the URL of the snapshot in the Wayback Machine, if the capture succeeded
+    pub fn wayback_url(&self) -> Option<String> {
+        self.wayback_url_with(PlaybackMode::Standard)
+    }
+
+    /// Get the URL of the snapshot played back in the given mode, if the capture succeeded
+    pub fn wayback_url_with(&self, mode: PlaybackMode) -> Option<String> {
+        match self {
+            SPN2CaptureStatus::Success {
+                original_url,
+                timestamp,
+                ..
+            } => Some(wayback_url(timestamp, original_url, mode)),
+            _ => None,
+        }
+    }
+
+    /// Get the counters of a successful capture, if the API reported them
+    pub fn counters(&self) -> Option<&CaptureCounters> {
+        match self {
+            SPN2CaptureStatus::Success { counters, .. } => counters.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Get the HTTP status the captured page was served with, if the API reported it
+    pub fn target_status(&self) -> Option<TargetStatus> {
+        match self {
+            SPN2CaptureStatus::Success { http_status, .. } => *http_status,
+            _ => None,
+        }
+    }
+
+    /// Check whether the capture succeeded but the page was served with an error status
+    ///
+    /// Such captures archived e.g. a 404 page, pipelines may want to filter them.
+    pub fn target_was_error(&self) -> bool {
+        self.target_status().is_some_and(|s| s.is_error())
+    }
+
+    /// Get the captured resources as parsed URLs
+    ///
+    /// Malformed entries are skipped.
+    pub fn resource_urls(&self) -> Vec<Url> {
+        let resources = match self {
+            SPN2CaptureStatus::Pending { resources, .. }
+            | SPN2CaptureStatus::Error { resources, .. }
+            | SPN2CaptureStatus::Success { resources, .. } => resources,
+        };
+        parse_urls(resources.iter().map(String::as_str))
+    }
+
+    /// Get the outlinks of a successful capture as parsed URLs
+    ///
+    /// Malformed entries are skipped.
+    pub fn outlink_urls(&self) -> Vec<Url> {
+        match self {
+            SPN2CaptureStatus::Success { outlinks, .. } => parse_urls(outlinks.urls()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get an actionable suggestion for a failed capture, if there is one
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.known_message().map(|known| known.suggestion())
+    }
+
+    /// Check whether the capture failed because the URL is excluded per site owner request
+    ///
+    /// Such captures will fail again when retried, so callers may want to
+    /// skip further URLs of the same host.
+    pub fn is_excluded_by_owner(&self) -> bool {
+        self.known_message() == Some(KnownMessage::ExcludedByOwner)
+    }
+
+    /// Get the time of a successful capture
+    ///
+    /// Returns `None` if the capture was not successful.
+    #[cfg(feature = "chrono")]
+    pub fn captured_at(&self) -> Result<Option<DateTime<Utc>>, Error> {
+        match self {
+            SPN2CaptureStatus::Success { timestamp, .. } => {
+                parse_wayback_timestamp(timestamp).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Get the snapshot URL of a legacy capture response
+///
+/// The snapshot is either given as Content-Location, or the request was
+/// redirected to it.
+fn legacy_snapshot_url(content_location: Option<&str>, final_url: &Url) -> Option<String> {
+    const WAYBACK_ORIGIN: &str = "https://web.archive.org";
+    match content_location {
+        Some(location) if location.starts_with("/web/") => {
+            Some(format!("{WAYBACK_ORIGIN}{location}"))
+        }
+        Some(location) if location.starts_with(WAYBACK_URL) => Some(location.to_string()),
+        _ => Some(final_url.as_str())
+            .filter(|url| url.starts_with(WAYBACK_URL))
+            .map(str::to_string),
+    }
+}
+
+/// Parse a Wayback timestamp in YYYYMMDDHHMMSS format
+#[cfg(feature = "chrono")]
+pub fn parse_wayback_timestamp(timestamp: &str) -> Result<DateTime<Utc>, Error> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S")
+        .map(|dt| dt.and_utc())
+        .map_err(|e| Error::InvalidResponse(format!("invalid timestamp {timestamp}: {e}")))
+}
+
+impl fmt::Display for SPN2CaptureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SPN2CaptureStatus::Pending { resources, .. } => {
+                write!(f, "pending, {} resources", resources.len())
+            }
+            SPN2CaptureStatus::Error {
+                status_ext,
+                message,
+                ..
+            } => write!(f, "error {status_ext}: {message}"),
+            SPN2CaptureStatus::Success {
+                timestamp,
+                resources,
+                outlinks,
+                ..
+            } => write!(
+                f,
+                "success {timestamp}, {} resources, {} outlinks",
+                resources.len(),
+                outlinks.len()
+            ),
+        }
+    }
+}
+
+/// The result of [`SPN2Client::validate_credentials`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CredentialsCheck {
+    /// The credentials were accepted
+    Valid(SPN2UserStatus),
+    /// The credentials were rejected
+    Invalid {
+        /// Why the credentials were rejected, as reported by the API if possible
+        reason: String,
+    },
+}
+
+impl CredentialsCheck {
+    /// Check whether the credentials were accepted
+    pub fn is_valid(&self) -> bool {
+        matches!(self, CredentialsCheck::Valid(_))
+    }
+
+    /// Interpret an error body of the user status endpoint, e.g
+    /// `{"status": "error", "message": "..."}`, as rejected credentials
+    fn from_error_body(body: &str) -> Option<Self> {
+        let json: Value = serde_json::from_str(body).ok()?;
+        if json.get("status")?.as_str()? != "error" {
+            return None;
+        }
+        let reason = json
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("credentials rejected")
+            .to_string();
+        Some(CredentialsCheck::Invalid { reason })
+    }
+}
+
+impl fmt::Display for SPN2UserStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} sessions available, {} processing",
+            self.available, self.processing
+        )
+    }
+}
+
+impl fmt::Display for SPN2SystemStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SPN2SystemStatus::Ok => write!(f, "ok"),
+            SPN2SystemStatus::Issues { description } => write!(f, "issues: {description}"),
+            SPN2SystemStatus::Critical => write!(f, "critical"),
         }
     }
 }
 
 impl SPN2SystemStatus {
-    fn from_json(json: serde_json::Value) -> Result<Self, Error> {
+    fn from_json(json: Value) -> Result<Self, Error> {
         let status = json
             .as_object()
             .and_then(|obj| obj.get("status"))
             .and_then(|status| status.as_str())
-            .ok_or_else(|| format!("invalid response: {json}"))?;
+            .ok_or_else(|| Error::InvalidResponse(json.to_string()))?;
         match status {
             "ok" => Ok(SPN2SystemStatus::Ok),
             msg => Ok(SPN2SystemStatus::Issues {
@@ -273,6 +1197,10 @@ impl SPN2SystemStatus {
     }
 }
 
+fn parse_urls<'a>(urls: impl Iterator<Item = &'a str>) -> Vec<Url> {
+    urls.filter_map(|url| Url::parse(url).ok()).collect()
+}
+
 fn serialize_bool_param<S>(b: &bool, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -292,6 +1220,21 @@ where
     }
 }
 
+fn deserialize_duration_secs<'de, D>(d: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let secs = f64::deserialize(d)?;
+    Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+}
+
+fn serialize_duration_secs<S>(d: &Duration, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_f64(d.as_secs_f64())
+}
+
 #[derive(Serialize)]
 struct SPN2CaptureRequestParams<'a> {
     url: &'a str,
@@ -310,12 +1253,20 @@ mod tests {
           "status":"pending",
           "job_id":"e70f23c7-9eca-4c78-826d-26930564d7c8",
           "resources": [
-            "https://ajax.googleapis.com/ajax/libs/jquery/1.7.2/jquery.min.js"
+            "https://ajax.googleapis.com/ajax/libs/jquery/1.7.2/jquery.min.js",
+            "not a url"
           ]
         }
         "#;
         let s: SPN2CaptureStatus = serde_json::from_str(status).unwrap();
         assert!(matches!(s, SPN2CaptureStatus::Pending { .. }));
+        assert_eq!(1, s.resource_urls().len());
+        if let SPN2CaptureStatus::Pending { extra, .. } = s {
+            assert_eq!(
+                Some("e70f23c7-9eca-4c78-826d-26930564d7c8"),
+                extra.get("job_id").and_then(Value::as_str)
+            );
+        }
     }
 
     #[test]
@@ -332,6 +1283,10 @@ mod tests {
         "#;
         let s: SPN2CaptureStatus = serde_json::from_str(status).unwrap();
         assert!(matches!(s, SPN2CaptureStatus::Error { .. }));
+        assert!(!s.is_transient_error());
+        let s: SPN2CaptureStatus =
+            serde_json::from_str(&status.replace("invalid-host-resolution", "celery")).unwrap();
+        assert!(s.is_transient_error());
     }
 
     #[test]
@@ -339,9 +1294,11 @@ mod tests {
         let status = r#"
         {
           "http_status": 200,
+          "first_archive": true,
           "counters": {
             "outlinks": 70,
-            "embeds": 21
+            "embeds": 21,
+            "screenshots": 1
           },
           "original_url": "https://example.com",
           "timestamp": "20221002124400",
@@ -358,6 +1315,162 @@ mod tests {
         "#;
         let s: SPN2CaptureStatus = serde_json::from_str(status).unwrap();
         assert!(matches!(s, SPN2CaptureStatus::Success { .. }));
+        if let SPN2CaptureStatus::Success {
+            duration_sec,
+            counters,
+            http_status,
+            first_archive,
+            ..
+        } = s.clone()
+        {
+            assert_eq!(Duration::from_millis(6214), duration_sec);
+            assert_eq!(
+                Some(StatusCode::OK),
+                http_status.and_then(|s| s.status_code())
+            );
+            assert!(first_archive);
+            let counters = counters.expect("counters should be present");
+            assert_eq!((70, 21), (counters.outlinks, counters.embeds));
+            assert_eq!(92, counters.total());
+        }
+        assert!(!s.target_was_error());
+        assert_eq!(
+            Some("https://web.archive.org/web/20221002124400/https://example.com"),
+            s.wayback_url().as_deref()
+        );
+        assert_eq!(
+            "success 20221002124400, 1 resources, 1 outlinks",
+            s.to_string()
+        );
+        let serialized = serde_json::to_string(&s).unwrap();
+        let deserialized: SPN2CaptureStatus = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(s, deserialized);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parse_timestamp() {
+        use chrono::{Datelike, Timelike};
+
+        let dt = parse_wayback_timestamp("20221002124400").unwrap();
+        assert_eq!((2022, 10, 2), (dt.year(), dt.month(), dt.day()));
+        assert_eq!((12, 44, 0), (dt.hour(), dt.minute(), dt.second()));
+        assert!(parse_wayback_timestamp("2022100212").is_err());
+    }
+
+    #[test]
+    fn deserialize_capture_status_success_outlinks_availability() {
+        let status = r#"
+        {
+          "original_url": "https://example.com",
+          "timestamp": "20221002124400",
+          "duration_sec": 6.214,
+          "status": "success",
+          "outlinks": {
+            "https://example.org/": "20221001000000",
+            "https://example.net/": null
+          },
+          "resources": []
+        }
+        "#;
+        let s: SPN2CaptureStatus = serde_json::from_str(status).unwrap();
+        assert!(matches!(
+            &s,
+            SPN2CaptureStatus::Success {
+                outlinks: Outlinks::Availability(_),
+                ..
+            }
+        ));
+        assert_eq!(2, s.outlink_urls().len());
+    }
+
+    #[test]
+    fn credentials_check_from_error_body() {
+        assert_eq!(
+            Some(CredentialsCheck::Invalid {
+                reason: "Invalid S3 keys.".to_string()
+            }),
+            CredentialsCheck::from_error_body(r#"{"status":"error","message":"Invalid S3 keys."}"#)
+        );
+        assert_eq!(
+            None,
+            CredentialsCheck::from_error_body(r#"{"available":1}"#)
+        );
+        assert_eq!(None, CredentialsCheck::from_error_body("<html>"));
+    }
+
+    #[test]
+    fn target_status() {
+        let not_found = TargetStatus::from(404);
+        assert_eq!(Some(StatusCode::NOT_FOUND), not_found.status_code());
+        assert!(not_found.is_error());
+        let nonstandard = TargetStatus::from(1234);
+        assert_eq!(None, nonstandard.status_code());
+        assert_eq!("1234", nonstandard.to_string());
+    }
+
+    #[test]
+    fn legacy_snapshot_location() {
+        let save_url = Url::parse("https://web.archive.org/save/https://example.com").unwrap();
+        assert_eq!(
+            Some("https://web.archive.org/web/20221002124400/https://example.com"),
+            legacy_snapshot_url(Some("/web/20221002124400/https://example.com"), &save_url)
+                .as_deref()
+        );
+        let redirected =
+            Url::parse("https://web.archive.org/web/20221002124400/https://example.com").unwrap();
+        assert_eq!(
+            Some(redirected.to_string()),
+            legacy_snapshot_url(None, &redirected)
+        );
+        assert_eq!(None, legacy_snapshot_url(None, &save_url));
+    }
+
+    #[tokio::test]
+    async fn anonymous_requires_credentials() {
+        let client = SPN2Client::anonymous().unwrap();
+        assert!(client.is_anonymous());
+        let err = client.get_user_status().await.unwrap_err();
+        assert!(matches!(err, Error::CredentialsRequired));
+    }
+
+    #[test]
+    fn parse_job_id() {
+        let job_id: JobId = "spn2-6e1b8d1e0bd7e5e4ab40d5bc7ac1e1a4f0f0c9c5"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            "spn2-6e1b8d1e0bd7e5e4ab40d5bc7ac1e1a4f0f0c9c5",
+            job_id.to_string()
+        );
+        assert!("".parse::<JobId>().is_err());
+        assert!("../user".parse::<JobId>().is_err());
+        assert!(serde_json::from_str::<JobId>(r#""spn2-abc""#).is_ok());
+        assert!(serde_json::from_str::<JobId>(r#""../user""#).is_err());
+    }
+
+    #[cfg(feature = "experimental-params")]
+    #[test]
+    fn serialize_experimental_params() {
+        let opt_params = SPN2CaptureRequestOptParams::default().experimental_param("delay", "3");
+        let params = SPN2CaptureRequestParams {
+            url: "example.com",
+            opt_params: &opt_params,
+        };
+        let params_encoded =
+            serde_urlencoded::to_string(params).expect("failed to serialize params");
+        assert!(params_encoded.ends_with("&delay=3"), "{params_encoded}");
+    }
+
+    #[test]
+    fn compose_cookies() {
+        let opt_params = SPN2CaptureRequestOptParams::default()
+            .with_cookie("session", "abc")
+            .locale("de-DE");
+        assert_eq!(
+            Some("session=abc; lang=de-DE"),
+            opt_params.capture_cookie.as_deref()
+        );
     }
 
     #[test]
@@ -386,6 +1499,8 @@ mod tests {
             use_user_agent: Some("Dummy".to_string()),
             target_username: None,
             target_password: None,
+            basis: Some("cited-in:case-123".parse().unwrap()),
+            experimental: Default::default(),
         };
         let params = SPN2CaptureRequestParams {
             url: "example.com",
@@ -399,4 +1514,20 @@ mod tests {
                         if_not_archived_within=1&use_user_agent=Dummy";
         assert_eq!(expected, params_encoded);
     }
+
+    #[test]
+    fn refuse_proxy_for_custom_http_client() {
+        let proxy = || Proxy::all("http://localhost:8080").unwrap();
+        let built = SPN2Client::builder().anonymous().build().unwrap();
+        assert!(built.with_proxy(proxy()).is_ok());
+        let custom = SPN2Client::builder()
+            .anonymous()
+            .http_client(reqwest::Client::new())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            custom.with_proxy(proxy()),
+            Err(Error::CustomHttpClient)
+        ));
+    }
 }
@@ -0,0 +1,168 @@
+//! Documented limits of the SPN2 API's capture parameters
+//!
+//! All limits live in the [`LIMITS`] table, so that an update of the API
+//! docs only requires touching the table and its tests. How requests that
+//! exceed the limits are handled is set with
+//! [`SPN2ClientBuilder::limit_mode`](crate::SPN2ClientBuilder::limit_mode).
+
+use std::{borrow::Cow, fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::SPN2CaptureRequestOptParams;
+
+/// The documented bounds of a duration parameter
+#[derive(Debug, Clone, Copy)]
+pub struct DurationLimit {
+    /// The name of the parameter
+    pub param: &'static str,
+    /// The smallest accepted value
+    pub min: Duration,
+    /// The largest accepted value, if there is one
+    pub max: Option<Duration>,
+    get: fn(&SPN2CaptureRequestOptParams) -> Option<Duration>,
+    set: fn(&mut SPN2CaptureRequestOptParams, Duration),
+}
+
+/// The limits of all capture parameters that have documented bounds
+pub const LIMITS: &[DurationLimit] = &[
+    DurationLimit {
+        param: "js_behavior_timeout",
+        min: Duration::ZERO,
+        max: Some(Duration::from_secs(30)),
+        get: |p| p.js_behavior_timeout,
+        set: |p, d| p.js_behavior_timeout = Some(d),
+    },
+    // The API takes whole seconds, shorter durations would be sent as 0
+    DurationLimit {
+        param: "if_not_archived_within",
+        min: Duration::from_secs(1),
+        max: None,
+        get: |p| p.if_not_archived_within,
+        set: |p, d| p.if_not_archived_within = Some(d),
+    },
+];
+
+impl DurationLimit {
+    fn clamp(&self, value: Duration) -> Duration {
+        let value = value.max(self.min);
+        self.max.map_or(value, |max| value.min(max))
+    }
+}
+
+/// A capture parameter that exceeds its documented limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitViolation {
+    /// The name of the parameter
+    pub param: &'static str,
+    /// The given value
+    pub value: Duration,
+    /// The closest value within the limit
+    pub clamped: Duration,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {:?} is out of limits, closest allowed value is {:?}",
+            self.param, self.value, self.clamped
+        )
+    }
+}
+
+/// How capture parameters that exceed the documented limits are handled
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LimitMode {
+    /// Replace the value with the closest one within the limit
+    #[default]
+    Clamp,
+    /// Fail the request with [`Error::ParamOutOfLimits`](crate::Error::ParamOutOfLimits)
+    Reject,
+    /// Send the value as given
+    Ignore,
+}
+
+impl SPN2CaptureRequestOptParams {
+    /// Get the parameters that exceed the documented limits of the API
+    pub fn limit_violations(&self) -> Vec<LimitViolation> {
+        LIMITS
+            .iter()
+            .filter_map(|limit| {
+                let value = (limit.get)(self)?;
+                let clamped = limit.clamp(value);
+                (clamped != value).then_some(LimitViolation {
+                    param: limit.param,
+                    value,
+                    clamped,
+                })
+            })
+            .collect()
+    }
+
+    /// Apply the limit mode, returning the parameters to send
+    pub(crate) fn within_limits(&self, mode: LimitMode) -> Result<Cow<'_, Self>, LimitViolation> {
+        let violations = match mode {
+            LimitMode::Ignore => return Ok(Cow::Borrowed(self)),
+            _ => self.limit_violations(),
+        };
+        if violations.is_empty() {
+            return Ok(Cow::Borrowed(self));
+        }
+        if mode == LimitMode::Reject {
+            return Err(violations[0].clone());
+        }
+        let mut params = self.clone();
+        for violation in violations {
+            if let Some(limit) = LIMITS.iter().find(|l| l.param == violation.param) {
+                (limit.set)(&mut params, violation.clamped);
+            }
+        }
+        Ok(Cow::Owned(params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_or_reject() {
+        let params = SPN2CaptureRequestOptParams {
+            js_behavior_timeout: Some(Duration::from_secs(60)),
+            if_not_archived_within: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let violations = params.limit_violations();
+        assert_eq!(1, violations.len());
+        assert_eq!("js_behavior_timeout", violations[0].param);
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            params
+                .within_limits(LimitMode::Clamp)
+                .unwrap()
+                .js_behavior_timeout
+        );
+        assert!(params.within_limits(LimitMode::Reject).is_err());
+        assert!(matches!(
+            params.within_limits(LimitMode::Ignore),
+            Ok(Cow::Borrowed(_))
+        ));
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        for limit in LIMITS {
+            assert!(
+                limit.max.is_none_or(|max| limit.min <= max),
+                "{}",
+                limit.param
+            );
+            let mut params = SPN2CaptureRequestOptParams::default();
+            (limit.set)(&mut params, limit.min);
+            assert_eq!(Some(limit.min), (limit.get)(&params), "{}", limit.param);
+        }
+    }
+}
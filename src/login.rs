@@ -0,0 +1,140 @@
+use std::future::Future;
+
+use reqwest::StatusCode;
+
+use crate::{
+    CaptureError, CaptureOutcome, Error, SPN2CaptureRequestOptParams, SPN2CaptureStatus,
+    SPN2Client, WaitOptions,
+};
+
+/// The `status_ext` codes of capture errors caused by a missing or expired login
+const AUTH_STATUS_EXTS: &[&str] = &["error:unauthorized", "error:no-access"];
+
+impl SPN2Client {
+    /// Capture a page that requires a login, using the cookies of a fresh session
+    ///
+    /// The `login` step logs in at the target site and returns the cookies of
+    /// the session in `Cookie` header format, e.g. `session=abc; user=42`.
+    /// They are appended to the `capture_cookie` of the parameters, separated
+    /// by `; `, so cookies set by the caller, e.g. with
+    /// [`SPN2CaptureRequestOptParams::locale`], are kept. If the capture
+    /// looks like it failed for a missing login, i.e. the page was served
+    /// with 401 or 403 or the job failed with `error:unauthorized` or
+    /// `error:no-access`, the session is refreshed by logging in again and
+    /// the capture is submitted once more.
+    ///
+    /// A failed login fails the capture with [`Error::LoginFailed`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(client: &spn::SPN2Client) -> Result<(), spn::CaptureError> {
+    /// let outcome = client
+    ///     .capture_with_login(
+    ///         "https://example.com/members",
+    ///         &Default::default(),
+    ///         &Default::default(),
+    ///         || async { Ok::<_, std::io::Error>("session=abc".to_string()) },
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn capture_with_login<F, Fut, E>(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+        wait_opts: &WaitOptions,
+        mut login: F,
+    ) -> Result<CaptureOutcome, CaptureError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<String, E>>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let mut refreshed = false;
+        loop {
+            let cookies = login()
+                .await
+                .map_err(|e| CaptureError::SubmitFailed(Error::LoginFailed(e.into())))?;
+            let opt_params = with_login_cookies(opt_params, cookies);
+            let result = self.capture_and_wait(url, &opt_params, wait_opts).await;
+            let status = match &result {
+                Ok(outcome) => &outcome.status,
+                Err(CaptureError::CaptureFailed(outcome)) => &outcome.status,
+                Err(_) => return result,
+            };
+            if refreshed || !needs_login(status) {
+                return result;
+            }
+            refreshed = true;
+        }
+    }
+}
+
+/// Append the cookies of a login session to the cookies of the parameters
+fn with_login_cookies(
+    opt_params: &SPN2CaptureRequestOptParams,
+    cookies: String,
+) -> SPN2CaptureRequestOptParams {
+    let capture_cookie = match opt_params.capture_cookie.as_deref() {
+        Some(existing) if !existing.is_empty() && !cookies.is_empty() => {
+            format!("{existing}; {cookies}")
+        }
+        Some(existing) if cookies.is_empty() => existing.to_string(),
+        _ => cookies,
+    };
+    SPN2CaptureRequestOptParams {
+        capture_cookie: Some(capture_cookie),
+        ..opt_params.clone()
+    }
+}
+
+/// Check whether the capture looks like it failed for a missing login
+fn needs_login(status: &SPN2CaptureStatus) -> bool {
+    match status {
+        SPN2CaptureStatus::Error { status_ext, .. } => {
+            AUTH_STATUS_EXTS.contains(&status_ext.as_str())
+        }
+        _ => status
+            .target_status()
+            .and_then(|s| s.status_code())
+            .is_some_and(|s| s == StatusCode::UNAUTHORIZED || s == StatusCode::FORBIDDEN),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn keep_existing_cookies() {
+        let params = SPN2CaptureRequestOptParams::default().locale("de-DE");
+        let params = with_login_cookies(&params, "session=abc".to_string());
+        assert_eq!(
+            Some("lang=de-DE; session=abc"),
+            params.capture_cookie.as_deref()
+        );
+        let params = with_login_cookies(&Default::default(), "session=abc".to_string());
+        assert_eq!(Some("session=abc"), params.capture_cookie.as_deref());
+    }
+
+    #[test]
+    fn recognize_missing_login() {
+        let error = |status_ext: &str| SPN2CaptureStatus::Error {
+            exception: None,
+            status_ext: status_ext.to_string(),
+            message: String::new(),
+            resources: Vec::new(),
+            extra: Map::new(),
+        };
+        assert!(needs_login(&error("error:unauthorized")));
+        assert!(!needs_login(&error("error:not-found")));
+        assert!(!needs_login(&SPN2CaptureStatus::Pending {
+            resources: Vec::new(),
+            extra: Map::new(),
+        }));
+    }
+}
@@ -0,0 +1,217 @@
+//! Interpretation of known SPN2 API messages
+//!
+//! Some behaviors of the API are only signaled via the free-text `message`
+//! of a response. This module keeps all knowledge about these messages in
+//! one place. The machine-readable `status_ext` code is always preferred,
+//! the message patterns are only used as a fallback.
+
+use std::borrow::Cow;
+
+/// A typed interpretation of a known API message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownMessage {
+    /// A recent snapshot of the URL exists and was reused instead of capturing again
+    RecentSnapshotReused,
+    /// The URL is excluded from the Wayback Machine per request of the site owner
+    ExcludedByOwner,
+    /// The URL is on the Save Page Now block list
+    BlockedUrl,
+    /// The target site blocks Save Page Now, e.g. by rate limiting its crawler
+    BlockedByTarget,
+    /// The host has been captured too many times today
+    DailyCaptureLimit,
+    /// The user has too many active capture sessions
+    SessionLimit,
+    /// Too many requests were sent in a short time
+    TooManyRequests,
+    /// The host of the URL could not be resolved
+    HostResolutionFailed,
+    /// No capture browsers were available, the job may succeed when retried
+    NoBrowsersAvailable,
+}
+
+impl KnownMessage {
+    /// Get an actionable suggestion for how to deal with the message
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            KnownMessage::RecentSnapshotReused => {
+                "lower if_not_archived_within to force a new capture"
+            }
+            KnownMessage::ExcludedByOwner => {
+                "the site owner excluded this URL, skip further URLs of this host"
+            }
+            KnownMessage::BlockedUrl => "the URL can not be captured with Save Page Now",
+            KnownMessage::BlockedByTarget => {
+                "the site is blocking Save Page Now, capture its URLs again later"
+            }
+            KnownMessage::DailyCaptureLimit => "try capturing URLs of this host again tomorrow",
+            KnownMessage::SessionLimit => {
+                "wait for active captures to finish, see the user status for available sessions"
+            }
+            KnownMessage::TooManyRequests => "slow down and retry later",
+            KnownMessage::HostResolutionFailed => "check the URL for typos",
+            KnownMessage::NoBrowsersAvailable => "retry the capture in a few minutes",
+        }
+    }
+}
+
+/// `status_ext` codes with a known interpretation
+const STATUS_EXT_TABLE: &[(&str, KnownMessage)] = &[
+    ("error:blocked", KnownMessage::BlockedByTarget),
+    ("error:blocked-url", KnownMessage::BlockedUrl),
+    (
+        "error:too-many-daily-captures",
+        KnownMessage::DailyCaptureLimit,
+    ),
+    ("error:user-session-limit", KnownMessage::SessionLimit),
+    ("error:too-many-requests", KnownMessage::TooManyRequests),
+    (
+        "error:invalid-host-resolution",
+        KnownMessage::HostResolutionFailed,
+    ),
+    (
+        "error:no-browsers-available",
+        KnownMessage::NoBrowsersAvailable,
+    ),
+];
+
+/// Message patterns with a known interpretation
+///
+/// A message matches a pattern if it contains all of the pattern's
+/// fragments, ignoring ASCII case.
+const MESSAGE_PATTERNS: &[(&[&str], KnownMessage)] = &[
+    (
+        &["same snapshot had been made"],
+        KnownMessage::RecentSnapshotReused,
+    ),
+    (&["excluded", "site owner"], KnownMessage::ExcludedByOwner),
+    (&["url is excluded"], KnownMessage::ExcludedByOwner),
+    (&["block list"], KnownMessage::BlockedUrl),
+    (
+        &["captured", "times today"],
+        KnownMessage::DailyCaptureLimit,
+    ),
+    (&["active sessions"], KnownMessage::SessionLimit),
+    (&["too many requests"], KnownMessage::TooManyRequests),
+    (&["resolve host"], KnownMessage::HostResolutionFailed),
+    (
+        &["no browsers available"],
+        KnownMessage::NoBrowsersAvailable,
+    ),
+];
+
+/// Interpret a `status_ext` code and/or message of an API response
+///
+/// The `status_ext` code takes precedence over the message.
+pub fn interpret(status_ext: Option<&str>, message: Option<&str>) -> Option<KnownMessage> {
+    status_ext
+        .and_then(interpret_status_ext)
+        .or_else(|| message.and_then(interpret_message))
+}
+
+/// Interpret a `status_ext` code
+pub fn interpret_status_ext(status_ext: &str) -> Option<KnownMessage> {
+    STATUS_EXT_TABLE
+        .iter()
+        .find(|(code, _)| *code == status_ext)
+        .map(|(_, known)| *known)
+}
+
+/// Interpret a free-text message
+pub fn interpret_message(message: &str) -> Option<KnownMessage> {
+    let message = message.to_ascii_lowercase();
+    MESSAGE_PATTERNS
+        .iter()
+        .find(|(fragments, _)| fragments.iter().all(|f| message.contains(f)))
+        .map(|(_, known)| *known)
+}
+
+/// Sanitize a message for single-line output, e.g in a terminal or a CSV cell
+///
+/// Control characters, including line breaks, are replaced with spaces and
+/// messages longer than `max_chars` characters are cut off with an ellipsis.
+/// Messages are never cut inside a character.
+pub fn sanitize(message: &str, max_chars: usize) -> Cow<'_, str> {
+    let needs_cut = message.chars().nth(max_chars).is_some();
+    if !needs_cut && !message.chars().any(char::is_control) {
+        return Cow::Borrowed(message);
+    }
+    let mut sanitized: String = message
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(if needs_cut {
+            max_chars.saturating_sub(1)
+        } else {
+            max_chars
+        })
+        .collect();
+    if needs_cut {
+        sanitized.push('…');
+    }
+    Cow::Owned(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpret_known_messages() {
+        let cases = [
+            (
+                "The same snapshot had been made 4 minutes ago. \
+                 You can make new capture of this URL after 1 hour.",
+                KnownMessage::RecentSnapshotReused,
+            ),
+            (
+                "This URL is excluded from the Wayback Machine per site owner request.",
+                KnownMessage::ExcludedByOwner,
+            ),
+            (
+                "This host has been already captured 50.0 times today. \
+                 Please try again tomorrow.",
+                KnownMessage::DailyCaptureLimit,
+            ),
+            (
+                "Couldn't resolve host for http://example5123.com.",
+                KnownMessage::HostResolutionFailed,
+            ),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(Some(expected), interpret_message(message), "{message}");
+        }
+        assert_eq!(None, interpret_message("Something unexpected happened."));
+    }
+
+    #[test]
+    fn sanitize_messages() {
+        assert!(matches!(sanitize("short", 10), Cow::Borrowed("short")));
+        assert_eq!("line one line two", sanitize("line one\nline two", 80));
+        assert_eq!("ääää…", sanitize("äääääääää", 5));
+        assert_eq!(" [31mred", sanitize("\u{1b}[31mred", 80));
+    }
+
+    #[test]
+    fn status_ext_takes_precedence() {
+        let known = interpret(
+            Some("error:blocked-url"),
+            Some("Couldn't resolve host for http://example5123.com."),
+        );
+        assert_eq!(Some(KnownMessage::BlockedUrl), known);
+        let known = interpret(
+            Some("error:unknown"),
+            Some("Couldn't resolve host for http://example5123.com."),
+        );
+        assert_eq!(Some(KnownMessage::HostResolutionFailed), known);
+    }
+
+    #[test]
+    fn blocked_is_not_excluded() {
+        let known = interpret(
+            Some("error:blocked"),
+            Some("This URL is excluded from the Wayback Machine per site owner request."),
+        );
+        assert_eq!(Some(KnownMessage::BlockedByTarget), known);
+    }
+}
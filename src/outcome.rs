@@ -0,0 +1,259 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BotChallenge, CaptureBasis, CaptureHandle, Error, JobId, LiveComparison,
+    SPN2CaptureRequestOptParams, SPN2CaptureStatus, SPN2Client, StalePendingPolicy, WaitOptions,
+};
+
+/// The outcome of a capture, bundling the request with its final status
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CaptureOutcome {
+    /// The URL that was requested to be captured
+    pub url: String,
+    /// The ID of the capture job
+    pub job_id: JobId,
+    /// The final status of the capture job
+    pub status: SPN2CaptureStatus,
+    /// The URL of the snapshot in the Wayback Machine, if the capture succeeded
+    pub wayback_url: Option<String>,
+    /// The policy that was applied because the job stayed pending for too long
+    pub stale_pending: Option<StalePendingPolicy>,
+    /// Why the page was archived, as given in the capture parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basis: Option<CaptureBasis>,
+    /// The capture parameters of the request
+    ///
+    /// Not serialized, a deserialized outcome has the default parameters.
+    #[serde(skip)]
+    pub opt_params: SPN2CaptureRequestOptParams,
+}
+
+impl CaptureOutcome {
+    pub(crate) fn new(
+        url: String,
+        job_id: JobId,
+        status: SPN2CaptureStatus,
+        stale_pending: Option<StalePendingPolicy>,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Self {
+        let wayback_url = status.wayback_url();
+        Self {
+            url,
+            job_id,
+            status,
+            wayback_url,
+            stale_pending,
+            basis: opt_params.basis.clone(),
+            opt_params: opt_params.clone(),
+        }
+    }
+
+    /// Check whether the capture succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, SPN2CaptureStatus::Success { .. })
+    }
+
+    /// Prepare submitting the URL again with modified capture parameters
+    ///
+    /// The parameters start out as those of the original request, e.g. to
+    /// add a cookie or a longer timeout after reviewing a failure. The basis
+    /// of the original request is kept unless it is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(client: &spn::SPN2Client, outcome: spn::CaptureOutcome) -> Result<(), spn::Error> {
+    /// let handle = outcome
+    ///     .resubmit_with(|params| params.capture_cookie = Some("session=abc".to_string()))
+    ///     .submit(client)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resubmit_with(
+        &self,
+        modify: impl FnOnce(&mut SPN2CaptureRequestOptParams),
+    ) -> Resubmission {
+        let mut opt_params = self.opt_params.clone();
+        if opt_params.basis.is_none() {
+            opt_params.basis = self.basis.clone();
+        }
+        modify(&mut opt_params);
+        Resubmission {
+            url: self.url.clone(),
+            opt_params,
+        }
+    }
+}
+
+/// A capture request prepared from an earlier outcome, see [`CaptureOutcome::resubmit_with`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resubmission {
+    /// The URL to capture
+    pub url: String,
+    /// The capture parameters
+    pub opt_params: SPN2CaptureRequestOptParams,
+}
+
+impl Resubmission {
+    /// Submit the capture request
+    pub async fn submit<'a>(&self, client: &'a SPN2Client) -> Result<CaptureHandle<'a>, Error> {
+        client.request_capture(&self.url, &self.opt_params).await
+    }
+
+    /// Submit the capture request and wait until the capture job is finished
+    ///
+    /// See [`SPN2Client::capture_and_wait`].
+    pub async fn capture_and_wait(
+        &self,
+        client: &SPN2Client,
+        wait_opts: &WaitOptions,
+    ) -> Result<CaptureOutcome, CaptureError> {
+        client
+            .capture_and_wait(&self.url, &self.opt_params, wait_opts)
+            .await
+    }
+}
+
+/// The ways a capture can fail, distinguished by how they can be remediated
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CaptureError {
+    /// The capture request could not be submitted
+    ///
+    /// Submitting the URL again may succeed.
+    SubmitFailed(Error),
+    /// The capture job ran and failed
+    ///
+    /// The outcome's status tells why, see [`SPN2CaptureStatus::known_message`].
+    CaptureFailed(Box<CaptureOutcome>),
+    /// The status of the capture job could not be tracked
+    ///
+    /// The job may still finish, its status can be requested again later.
+    TrackingFailed {
+        /// The ID of the capture job
+        job_id: JobId,
+        /// The error that occurred while tracking the job
+        source: Error,
+    },
+    /// The capture job succeeded, but its snapshot could not be found
+    ///
+    /// The snapshot may only become available later.
+    NotVerified(Box<CaptureOutcome>),
+    /// The snapshot differs too much from the live page
+    ///
+    /// The capture likely saved a cookie wall or a bot check instead of the
+    /// real content.
+    LiveMismatch(Box<CaptureOutcome>, LiveComparison),
+    /// The snapshot shows a bot challenge instead of the page
+    ///
+    /// Capturing the URL again will likely hit the challenge again, it needs
+    /// a manual capture or one with the cookies of a solved challenge.
+    BlockedByBotProtection(Box<CaptureOutcome>, BotChallenge),
+}
+
+impl CaptureError {
+    /// Get the stable machine-readable code of this error
+    ///
+    /// For submit and tracking failures this is the code of the underlying error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CaptureError::SubmitFailed(e) => e.code(),
+            CaptureError::CaptureFailed(_) => "spn::capture_failed",
+            CaptureError::TrackingFailed { source, .. } => source.code(),
+            CaptureError::NotVerified(_) => "spn::not_verified",
+            CaptureError::LiveMismatch(..) => "spn::live_mismatch",
+            CaptureError::BlockedByBotProtection(..) => "spn::blocked_by_bot_protection",
+        }
+    }
+
+    /// Get the ID of the capture job, if the capture request was submitted
+    pub fn job_id(&self) -> Option<&JobId> {
+        match self {
+            CaptureError::SubmitFailed(_) => None,
+            CaptureError::CaptureFailed(outcome) => Some(&outcome.job_id),
+            CaptureError::TrackingFailed { job_id, .. } => Some(job_id),
+            CaptureError::NotVerified(outcome)
+            | CaptureError::LiveMismatch(outcome, _)
+            | CaptureError::BlockedByBotProtection(outcome, _) => Some(&outcome.job_id),
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::SubmitFailed(e) => write!(f, "failed to submit capture: {e}"),
+            CaptureError::CaptureFailed(outcome) => {
+                write!(f, "capture of {} failed: {}", outcome.url, outcome.status)
+            }
+            CaptureError::TrackingFailed { job_id, source } => {
+                write!(f, "failed to track capture job {job_id}: {source}")
+            }
+            CaptureError::NotVerified(outcome) => {
+                write!(f, "snapshot of {} could not be found", outcome.url)
+            }
+            CaptureError::LiveMismatch(outcome, comparison) => write!(
+                f,
+                "snapshot of {} differs from the live page (similarity {:.2})",
+                outcome.url, comparison.similarity
+            ),
+            CaptureError::BlockedByBotProtection(outcome, challenge) => {
+                write!(f, "snapshot of {} shows a {challenge}", outcome.url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CaptureError::SubmitFailed(e) => Some(e),
+            CaptureError::CaptureFailed(_)
+            | CaptureError::NotVerified(_)
+            | CaptureError::LiveMismatch(..)
+            | CaptureError::BlockedByBotProtection(..) => None,
+            CaptureError::TrackingFailed { source, .. } => Some(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn resubmit_with_modified_params() {
+        let opt_params = SPN2CaptureRequestOptParams {
+            capture_all: true,
+            ..Default::default()
+        }
+        .with_basis("cited-in:case-123".parse().unwrap());
+        let outcome = CaptureOutcome::new(
+            "https://example.com/".to_string(),
+            "spn2-abc".parse().unwrap(),
+            SPN2CaptureStatus::Pending {
+                resources: Vec::new(),
+                extra: Map::new(),
+            },
+            None,
+            &opt_params,
+        );
+        let resubmission = outcome.resubmit_with(|params| {
+            params.js_behavior_timeout = Some(Duration::from_secs(30));
+        });
+        assert_eq!("https://example.com/", resubmission.url);
+        assert!(resubmission.opt_params.capture_all);
+        assert_eq!(outcome.basis, resubmission.opt_params.basis);
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            resubmission.opt_params.js_behavior_timeout
+        );
+    }
+}
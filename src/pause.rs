@@ -0,0 +1,56 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::time;
+
+/// How long requests pause after a rate limit response without Retry-After
+pub(crate) const DEFAULT_RATE_LIMIT_PAUSE: Duration = Duration::from_secs(10);
+
+/// A pause shared by all requests of a client after a rate limit response
+///
+/// Without it, the other tasks sharing a client keep sending requests while
+/// one of them waits out the rate limit, and make it worse.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitPause(Mutex<Option<Instant>>);
+
+impl RateLimitPause {
+    /// Pause all requests for at least the given duration
+    pub(crate) fn extend(&self, pause: Duration) {
+        let until = Instant::now() + pause;
+        let mut current = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_none_or(|current| current < until) {
+            *current = Some(until);
+        }
+    }
+
+    /// Get how long requests are still paused
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        let until = *self.0.lock().unwrap_or_else(|e| e.into_inner());
+        until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Wait until requests are not paused anymore
+    pub(crate) async fn wait(&self) {
+        while let Some(remaining) = self.remaining() {
+            time::sleep(remaining).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_keeps_longest_pause() {
+        let pause = RateLimitPause::default();
+        assert_eq!(None, pause.remaining());
+        pause.extend(Duration::from_secs(60));
+        pause.extend(Duration::from_secs(1));
+        assert!(pause.remaining().unwrap() > Duration::from_secs(50));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::WAYBACK_URL;
+
+/// How the Wayback Machine plays back a snapshot
+///
+/// Each mode corresponds to a suffix of the timestamp in the snapshot URL,
+/// e.g. `id_` in `https://web.archive.org/web/20221002124400id_/https://example.com`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PlaybackMode {
+    /// The snapshot with the Wayback Machine toolbar and rewritten links
+    #[default]
+    Standard,
+    /// The original content as captured, without rewriting (`id_`)
+    Raw,
+    /// The snapshot without the toolbar, for embedding in an iframe (`if_`)
+    Iframe,
+    /// The snapshot served as an image (`im_`)
+    Image,
+    /// The snapshot served as JavaScript (`js_`)
+    JavaScript,
+    /// The snapshot served as CSS (`cs_`)
+    Css,
+}
+
+impl PlaybackMode {
+    /// Get the suffix that is appended to the timestamp in a snapshot URL
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            PlaybackMode::Standard => "",
+            PlaybackMode::Raw => "id_",
+            PlaybackMode::Iframe => "if_",
+            PlaybackMode::Image => "im_",
+            PlaybackMode::JavaScript => "js_",
+            PlaybackMode::Css => "cs_",
+        }
+    }
+}
+
+impl fmt::Display for PlaybackMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PlaybackMode::Standard => "standard",
+            PlaybackMode::Raw => "raw",
+            PlaybackMode::Iframe => "iframe",
+            PlaybackMode::Image => "image",
+            PlaybackMode::JavaScript => "javascript",
+            PlaybackMode::Css => "css",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Build the URL of a snapshot in the Wayback Machine
+///
+/// # Examples
+///
+/// ```
+/// use spn::PlaybackMode;
+///
+/// assert_eq!(
+///     "https://web.archive.org/web/20221002124400id_/https://example.com",
+///     spn::wayback_url("20221002124400", "https://example.com", PlaybackMode::Raw)
+/// );
+/// ```
+pub fn wayback_url(timestamp: &str, original_url: &str, mode: PlaybackMode) -> String {
+    format!("{WAYBACK_URL}/{timestamp}{}/{original_url}", mode.suffix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffixes() {
+        let url = |mode| wayback_url("20221002124400", "https://example.com", mode);
+        assert_eq!(
+            "https://web.archive.org/web/20221002124400/https://example.com",
+            url(PlaybackMode::Standard)
+        );
+        assert_eq!(
+            "https://web.archive.org/web/20221002124400if_/https://example.com",
+            url(PlaybackMode::Iframe)
+        );
+        assert_eq!(
+            "https://web.archive.org/web/20221002124400im_/https://example.com",
+            url(PlaybackMode::Image)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use reqwest::{NoProxy, Proxy};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::Error;
+
+/// The configuration of a proxy that all requests are sent through
+///
+/// HTTP and HTTPS proxies are always supported, SOCKS5 proxies
+/// (`socks5://` URLs) with the `socks` feature.
+///
+/// # Examples
+///
+/// ```
+/// let client = spn::SPN2Client::builder()
+///     .credentials("access", "secret")
+///     .proxy_config(spn::ProxyConfig {
+///         no_proxy: vec!["localhost".to_string(), "10.0.0.0/8".to_string()],
+///         ..spn::ProxyConfig::new("http://proxy.example.com:3128")
+///     })
+///     .build()
+///     .expect("failed to create spn2 client");
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProxyConfig {
+    /// The URL of the proxy
+    pub url: String,
+    /// The username for basic authentication at the proxy
+    #[serde(default)]
+    pub username: Option<String>,
+    /// The password for basic authentication at the proxy
+    #[serde(default)]
+    pub password: Option<SecretString>,
+    /// Hosts, domains and IP ranges that are reached without the proxy
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Create the configuration of a proxy without authentication
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            username: None,
+            password: None,
+            no_proxy: Vec::new(),
+        }
+    }
+
+    /// Set the credentials for basic authentication at the proxy
+    pub fn basic_auth(mut self, username: &str, password: impl Into<SecretString>) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Convert to a proxy of the HTTP client
+    ///
+    /// Fails if the URL is not a valid proxy URL.
+    pub(crate) fn to_proxy(&self) -> Result<Proxy, Error> {
+        let mut proxy = Proxy::all(&self.url)?;
+        if let Some(username) = &self.username {
+            let password = self
+                .password
+                .as_ref()
+                .map(ExposeSecret::expose_secret)
+                .unwrap_or_default();
+            proxy = proxy.basic_auth(username, password);
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")));
+        }
+        Ok(proxy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_proxy_url() {
+        assert!(ProxyConfig::new("http://proxy.example.com:3128")
+            .basic_auth("user", "pass")
+            .to_proxy()
+            .is_ok());
+        assert!(ProxyConfig::new("not a url").to_proxy().is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde_json::Value;
+
+use crate::{Error, JobId, SPN2CaptureRequestOptParams, SPN2Client};
+
+/// Escape hatches to the API's responses, for behavior the typed methods do not model
+///
+/// The requests are built like those of the typed methods, with the same
+/// headers, authentication and timeout.
+impl SPN2Client {
+    /// Send a capture request for the given URL, returning the raw response
+    ///
+    /// Like all `send_*_raw` methods, the request is not retried and the
+    /// response status is not checked.
+    pub async fn send_capture_raw(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Result<Response, Error> {
+        Ok(self.capture_request(url, opt_params)?.send().await?)
+    }
+
+    /// Send a capture status request, returning the raw response
+    pub async fn send_capture_status_raw(&self, job_id: &JobId) -> Result<Response, Error> {
+        Ok(self.capture_status_request(job_id)?.send().await?)
+    }
+
+    /// Send a user status request, returning the raw response
+    pub async fn send_user_status_raw(&self) -> Result<Response, Error> {
+        Ok(self.user_status_request()?.send().await?)
+    }
+
+    /// Send a system status request, returning the raw response
+    pub async fn send_system_status_raw(&self) -> Result<Response, Error> {
+        Ok(self.system_status_request().send().await?)
+    }
+
+    /// Get the current status of a capture job as untyped JSON
+    ///
+    /// Unlike [`Self::send_capture_status_raw`], the request is retried and
+    /// unsuccessful responses are turned into errors, like for
+    /// [`Self::get_capture_status`]. Useful to access fields that are not
+    /// modeled yet.
+    pub async fn get_capture_status_raw(&self, job_id: &JobId) -> Result<Value, Error> {
+        self.get_json(|| self.capture_status_request(job_id)).await
+    }
+
+    /// Get the current status of the user as untyped JSON
+    pub async fn get_user_status_raw(&self) -> Result<Value, Error> {
+        self.get_json(|| self.user_status_request()).await
+    }
+
+    /// Get the current status of the SPN system as untyped JSON
+    pub async fn get_system_status_raw(&self) -> Result<Value, Error> {
+        self.get_json(|| Ok(self.system_status_request())).await
+    }
+
+    /// Send the request, retrying it, and parse the successful response as JSON
+    async fn get_json<F>(&self, request: F) -> Result<Value, Error>
+    where
+        F: Fn() -> Result<RequestBuilder, Error>,
+    {
+        self.retrying(|| async {
+            let resp = request()?.send().await?;
+            match resp.status() {
+                StatusCode::OK => Ok(resp.json().await?),
+                _ => Err(Error::from_response(&resp)),
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn raw_requests_require_credentials() {
+        let client = SPN2Client::anonymous().unwrap();
+        let job_id = "spn2-abc".parse().unwrap();
+        assert!(matches!(
+            client.send_capture_status_raw(&job_id).await,
+            Err(Error::CredentialsRequired)
+        ));
+        assert!(matches!(
+            client.get_user_status_raw().await,
+            Err(Error::CredentialsRequired)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Mutex;
+
+use reqwest::{Method, RequestBuilder, Url};
+use serde_json::Map;
+
+use crate::{
+    Error, JobId, SPN2CaptureRequestOptParams, SPN2CaptureResponse, SPN2CaptureStatus, SPN2Client,
+    SPN2SystemStatus, SPN2UserStatus, SpnApi,
+};
+
+/// A request that a [`RecordingClient`] would have sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RecordedRequest {
+    /// The HTTP method of the request
+    pub method: Method,
+    /// The URL of the request, including the query
+    pub url: Url,
+    /// The URL encoded form body of the request, if it has one
+    pub form: Option<String>,
+}
+
+/// A client that records the requests it would send instead of sending them
+///
+/// The requests are built exactly like the wrapped [`SPN2Client`] builds them,
+/// which makes this useful for auditing workflows written against [`SpnApi`]
+/// and for generating capture plans. No traffic is ever sent.
+///
+/// The responses are made up: captures get job IDs like `dry-run-0`, capture
+/// jobs stay pending, the user has one available session and the system is
+/// fine.
+#[derive(Debug)]
+pub struct RecordingClient {
+    client: SPN2Client,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl RecordingClient {
+    /// Create a recording client that builds requests like the given client
+    pub fn new(client: SPN2Client) -> Self {
+        Self {
+            client,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Get the requests recorded so far, in the order they were made
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().clone()
+    }
+
+    /// Take the requests recorded so far, clearing the record
+    pub fn take_requests(&self) -> Vec<RecordedRequest> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Record the request, returning its index in the record
+    fn record(&self, request: RequestBuilder) -> Result<usize, Error> {
+        let request = request.build()?;
+        let form = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned());
+        let mut requests = self.lock();
+        requests.push(RecordedRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            form,
+        });
+        Ok(requests.len() - 1)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<RecordedRequest>> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SpnApi for RecordingClient {
+    async fn submit_capture(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> Result<SPN2CaptureResponse, Error> {
+        let index = self.record(self.client.capture_request(url, opt_params)?)?;
+        Ok(SPN2CaptureResponse {
+            url: url.to_string(),
+            job_id: format!("dry-run-{index}").parse()?,
+            message: None,
+            extra: Map::new(),
+        })
+    }
+
+    async fn get_capture_status(&self, job_id: &JobId) -> Result<SPN2CaptureStatus, Error> {
+        self.record(self.client.capture_status_request(job_id)?)?;
+        Ok(SPN2CaptureStatus::Pending {
+            resources: Vec::new(),
+            extra: Map::new(),
+        })
+    }
+
+    async fn get_user_status(&self) -> Result<SPN2UserStatus, Error> {
+        self.record(self.client.user_status_request()?)?;
+        Ok(SPN2UserStatus {
+            available: 1,
+            processing: 0,
+            extra: Map::new(),
+        })
+    }
+
+    async fn get_system_status(&self) -> Result<SPN2SystemStatus, Error> {
+        self.record(self.client.system_status_request())?;
+        Ok(SPN2SystemStatus::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn capture_all(api: &impl SpnApi, urls: &[&str]) -> Result<Vec<JobId>, Error> {
+        let mut job_ids = Vec::new();
+        for url in urls {
+            let resp = api.submit_capture(url, &Default::default()).await?;
+            api.get_capture_status(&resp.job_id).await?;
+            job_ids.push(resp.job_id);
+        }
+        Ok(job_ids)
+    }
+
+    #[tokio::test]
+    async fn record_requests() {
+        let client =
+            RecordingClient::new(SPN2Client::builder().credentials("a", "s").build().unwrap());
+        let job_ids = capture_all(&client, &["https://example.com/"])
+            .await
+            .unwrap();
+        assert_eq!("dry-run-0", job_ids[0].as_str());
+        let requests = client.take_requests();
+        assert_eq!(2, requests.len());
+        assert_eq!(Method::POST, requests[0].method);
+        assert_eq!("https://web.archive.org/save", requests[0].url.as_str());
+        assert!(requests[0]
+            .form
+            .as_deref()
+            .unwrap()
+            .starts_with("url=https%3A%2F%2Fexample.com%2F"));
+        assert_eq!(Method::GET, requests[1].method);
+        assert!(requests[1].url.as_str().ends_with("/dry-run-0"));
+        assert!(client.requests().is_empty());
+    }
+}
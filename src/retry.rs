@@ -0,0 +1,518 @@
+use std::{collections::BTreeMap, fmt, future::Future, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::time;
+
+use crate::{
+    backoff::random_fraction, pause::DEFAULT_RATE_LIMIT_PAUSE, Backoff, BackoffConfig, Error,
+    ExponentialBackoff, SPN2Client, SPN2SystemStatus,
+};
+
+/// A class of errors that share how they are retried, see [`Error::class`]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// Connecting to the API or transferring data failed
+    Network,
+    /// A request timed out
+    Timeout,
+    /// The API rejected the credentials
+    AuthFailed,
+    /// The API rejected the request because too many requests were sent
+    RateLimited,
+    /// The API responded with a 5xx status
+    ServerError,
+    /// The API responded with an unexpected 4xx status
+    ClientError,
+    /// The API response could not be interpreted
+    InvalidResponse,
+    /// Errors that are never worth retrying, e.g. invalid input
+    Other,
+}
+
+/// How errors of one class are retried
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryRule {
+    /// How often a failed request is retried at most
+    pub max_retries: u32,
+    /// Whether to wait as long as a rate limit response's Retry-After header says
+    ///
+    /// Otherwise, or without such a header, the policy's backoff is used.
+    #[serde(default)]
+    pub respect_retry_after: bool,
+}
+
+impl RetryRule {
+    /// Never retry
+    pub const NEVER: RetryRule = RetryRule {
+        max_retries: 0,
+        respect_retry_after: false,
+    };
+
+    /// Retry up to the given amount of times
+    pub fn retries(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            respect_retry_after: false,
+        }
+    }
+}
+
+/// How failed requests to the SPN API are retried
+///
+/// The policy installed with [`crate::SPN2ClientBuilder::retry_policy`]
+/// applies to all requests of the client. [`StandardRetryPolicy`] suits most
+/// workloads, others can implement their own.
+pub trait RetryPolicy: fmt::Debug + Send + Sync {
+    /// Get the delay before the given retry of a request that failed with the error
+    ///
+    /// Retries are counted from 1, `previous` is the delay before the
+    /// previous retry. Returns `None` if the request should not be retried.
+    fn retry_delay(&self, error: &Error, retry: u32, previous: Duration) -> Option<Duration>;
+}
+
+/// A retry policy with a rule per error class and a backoff
+///
+/// Each [`ErrorClass`] can be given its own [`RetryRule`], classes without
+/// an override use the default rule. The default policy never retries. The
+/// delays between retries come from any [`Backoff`], by default an
+/// [`ExponentialBackoff`].
+///
+/// # Examples
+///
+/// Retry network errors up to 5 times, but never retry failed authentication:
+/// ```
+/// use spn::{ErrorClass, RetryRule, StandardRetryPolicy};
+///
+/// let policy = StandardRetryPolicy::default()
+///     .with_override(ErrorClass::Network, RetryRule::retries(5))
+///     .with_override(ErrorClass::AuthFailed, RetryRule::NEVER);
+/// ```
+///
+/// Retry with decorrelated jitter instead of exponential backoff:
+/// ```
+/// use std::time::Duration;
+///
+/// use spn::{DecorrelatedJitterBackoff, StandardRetryPolicy};
+///
+/// let policy = StandardRetryPolicy::recommended().with_backoff(DecorrelatedJitterBackoff {
+///     base: Duration::from_secs(1),
+///     cap: Duration::from_secs(30),
+/// });
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StandardRetryPolicy {
+    /// The rule for error classes without an override
+    #[serde(default)]
+    pub default: RetryRule,
+    /// The rules for specific error classes
+    #[serde(default)]
+    pub overrides: BTreeMap<ErrorClass, RetryRule>,
+    /// The delays between retries
+    ///
+    /// (De)serialized as a [`BackoffConfig`], an untagged backoff is read as an
+    /// [`ExponentialBackoff`]. Serializing a policy with a custom backoff
+    /// fails, since it can not be restored.
+    #[serde(
+        default = "default_backoff",
+        deserialize_with = "deserialize_backoff",
+        serialize_with = "serialize_backoff"
+    )]
+    pub backoff: Arc<dyn Backoff>,
+    /// The fraction of the backoff delay that is randomized, between 0 and 1
+    ///
+    /// Keeps many clients that failed at the same time from retrying in
+    /// lockstep. A jitter of 0.5 waits between half and all of the delay.
+    /// Values outside of 0 and 1 are clamped, NaN fails deserialization and
+    /// counts as 0 otherwise.
+    #[serde(default, deserialize_with = "deserialize_jitter")]
+    pub jitter: f64,
+}
+
+impl Default for StandardRetryPolicy {
+    fn default() -> Self {
+        Self {
+            default: RetryRule::default(),
+            overrides: BTreeMap::new(),
+            backoff: default_backoff(),
+            jitter: 0.0,
+        }
+    }
+}
+
+fn default_backoff() -> Arc<dyn Backoff> {
+    Arc::new(ExponentialBackoff::default())
+}
+
+fn deserialize_backoff<'de, D>(deserializer: D) -> Result<Arc<dyn Backoff>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    /// Untagged exponential backoffs are accepted as they were written before the tag
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Tagged(BackoffConfig),
+        Exponential(ExponentialBackoff),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Tagged(config) => config.into_backoff(),
+        Repr::Exponential(backoff) => Arc::new(backoff),
+    })
+}
+
+fn serialize_backoff<S>(backoff: &Arc<dyn Backoff>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match backoff.to_config() {
+        Some(config) => config.serialize(serializer),
+        None => Err(serde::ser::Error::custom(format!(
+            "custom backoff {backoff:?} can not be serialized"
+        ))),
+    }
+}
+
+fn deserialize_jitter<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let jitter = f64::deserialize(deserializer)?;
+    if jitter.is_nan() {
+        return Err(serde::de::Error::custom("jitter must be a number"));
+    }
+    Ok(jitter.clamp(0.0, 1.0))
+}
+
+impl StandardRetryPolicy {
+    /// A policy that suits most long-running archivers
+    ///
+    /// Network errors and timeouts are retried up to 5 times, server errors
+    /// up to 3 times and rate limits up to 3 times respecting Retry-After.
+    /// Other errors are not retried. Half of the backoff delay is randomized.
+    pub fn recommended() -> Self {
+        Self {
+            jitter: 0.5,
+            ..Self::default()
+        }
+        .with_override(ErrorClass::Network, RetryRule::retries(5))
+        .with_override(ErrorClass::Timeout, RetryRule::retries(5))
+        .with_override(ErrorClass::ServerError, RetryRule::retries(3))
+        .with_override(
+            ErrorClass::RateLimited,
+            RetryRule {
+                max_retries: 3,
+                respect_retry_after: true,
+            },
+        )
+    }
+
+    /// Set the fraction of the backoff delay that is randomized, clamped to 0 to 1
+    ///
+    /// # Panics
+    ///
+    /// Panics if the jitter is NaN.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        assert!(!jitter.is_nan(), "jitter must be a number");
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the backoff for the delays between retries
+    pub fn with_backoff(mut self, backoff: impl Backoff + 'static) -> Self {
+        self.backoff = Arc::new(backoff);
+        self
+    }
+
+    /// Set the rule for the given error class
+    pub fn with_override(mut self, class: ErrorClass, rule: RetryRule) -> Self {
+        self.overrides.insert(class, rule);
+        self
+    }
+
+    /// Get the rule for the given error class
+    pub fn rule(&self, class: ErrorClass) -> RetryRule {
+        self.overrides.get(&class).copied().unwrap_or(self.default)
+    }
+}
+
+impl RetryPolicy for StandardRetryPolicy {
+    fn retry_delay(&self, error: &Error, retry: u32, previous: Duration) -> Option<Duration> {
+        let rule = self.rule(error.class());
+        if retry > rule.max_retries {
+            return None;
+        }
+        match error {
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+            } if rule.respect_retry_after => Some(*retry_after),
+            _ => {
+                let delay = self.backoff.delay(retry, previous);
+                // the field is public, so it may still be out of range
+                let jitter = match self.jitter.is_nan() {
+                    true => 0.0,
+                    false => self.jitter.clamp(0.0, 1.0) * random_fraction(),
+                };
+                Some(delay.mul_f64(1.0 - jitter))
+            }
+        }
+    }
+}
+
+impl SPN2Client {
+    /// Run the request, retrying it according to the client's retry policy
+    ///
+    /// After a rate limit response, all requests of the client pause for as
+    /// long as the response's Retry-After header says, or 10 seconds.
+    pub(crate) async fn retrying<T, F, Fut>(&self, mut request: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut retry = 0;
+        let mut delay = Duration::ZERO;
+        loop {
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.admit(self).await?;
+            }
+            self.rate_limit_pause.wait().await;
+            let result = request().await;
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.record(result.as_ref().err());
+            }
+            let error = match result {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if let Error::RateLimited { retry_after } = &error {
+                self.rate_limit_pause
+                    .extend(retry_after.unwrap_or(DEFAULT_RATE_LIMIT_PAUSE));
+            }
+            retry += 1;
+            match self.retry_policy.retry_delay(&error, retry, delay) {
+                Some(next) => delay = next,
+                None => return Err(error),
+            }
+            let overloaded = matches!(
+                error.class(),
+                ErrorClass::ServerError | ErrorClass::RateLimited
+            );
+            if let Some(pause) = self.system_status_pause.as_ref().filter(|_| overloaded) {
+                if !pause.wait_while(|| self.system_has_issues()).await {
+                    return Err(error);
+                }
+            }
+            time::sleep(delay).await;
+        }
+    }
+
+    /// Check whether the system reports issues, unknown counts as no issues
+    async fn system_has_issues(&self) -> bool {
+        self.fetch_system_status()
+            .await
+            .is_ok_and(|status| status != SPN2SystemStatus::Ok)
+    }
+}
+
+/// How retries are paused while the system reports issues
+#[derive(Debug, Clone)]
+pub(crate) struct SystemStatusPause {
+    pub(crate) backoff: Arc<dyn Backoff>,
+    pub(crate) max_wait: Duration,
+}
+
+impl SystemStatusPause {
+    /// Wait while the system has issues, returning false if they outlast the maximum wait
+    async fn wait_while<F, Fut>(&self, mut has_issues: F) -> bool
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        let mut waited = Duration::ZERO;
+        let mut delay = Duration::ZERO;
+        let mut attempt = 0;
+        while has_issues().await {
+            attempt += 1;
+            delay = self.backoff.delay(attempt, delay);
+            if waited + delay > self.max_wait {
+                return false;
+            }
+            time::sleep(delay).await;
+            waited += delay;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn validate_jitter() {
+        assert_eq!(1.0, StandardRetryPolicy::default().with_jitter(2.0).jitter);
+        assert_eq!(0.0, StandardRetryPolicy::default().with_jitter(-1.0).jitter);
+        let policy: StandardRetryPolicy = serde_json::from_str(r#"{"jitter":1.5}"#).unwrap();
+        assert_eq!(1.0, policy.jitter);
+        let nan = serde::de::value::F64Deserializer::<serde::de::value::Error>::new(f64::NAN);
+        assert!(deserialize_jitter(nan).is_err());
+        let policy = StandardRetryPolicy {
+            jitter: f64::NAN,
+            ..StandardRetryPolicy::recommended()
+        };
+        let server_error = Error::UnexpectedStatus(StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            policy.retry_delay(&server_error, 1, Duration::ZERO)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "jitter must be a number")]
+    fn reject_nan_jitter() {
+        let _ = StandardRetryPolicy::default().with_jitter(f64::NAN);
+    }
+
+    #[test]
+    fn per_class_overrides() {
+        let policy = StandardRetryPolicy::recommended();
+        let rate_limited = Error::RateLimited {
+            retry_after: Some(Duration::from_secs(42)),
+        };
+        assert_eq!(
+            Some(Duration::from_secs(42)),
+            policy.retry_delay(&rate_limited, 1, Duration::ZERO)
+        );
+        assert_eq!(None, policy.retry_delay(&rate_limited, 4, Duration::ZERO));
+        assert_eq!(
+            None,
+            policy.retry_delay(&Error::AuthFailed, 1, Duration::ZERO)
+        );
+        let server_error = Error::UnexpectedStatus(StatusCode::BAD_GATEWAY);
+        let delay = policy
+            .retry_delay(&server_error, 2, Duration::ZERO)
+            .unwrap();
+        assert!(delay > Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        let exact = StandardRetryPolicy {
+            jitter: 0.0,
+            ..policy
+        };
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            exact.retry_delay(&server_error, 2, Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn deserialize_policy() {
+        let policy: StandardRetryPolicy = serde_json::from_str(
+            r#"{
+                "default": {"max_retries": 1},
+                "overrides": {"auth_failed": {"max_retries": 0}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(RetryRule::retries(1), policy.rule(ErrorClass::Network));
+        assert_eq!(RetryRule::NEVER, policy.rule(ErrorClass::AuthFailed));
+        let policy: StandardRetryPolicy =
+            serde_json::from_str(r#"{"backoff": {"base": {"secs": 3, "nanos": 0}, "multiplier": 1.0, "cap": {"secs": 3, "nanos": 0}}}"#)
+                .unwrap();
+        assert_eq!(
+            Duration::from_secs(3),
+            policy.backoff.delay(5, Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn custom_backoff() {
+        let policy = StandardRetryPolicy::recommended().with_backoff(crate::FixedBackoff {
+            delay: Duration::from_secs(7),
+        });
+        let delay = policy
+            .retry_delay(
+                &Error::UnexpectedStatus(StatusCode::BAD_GATEWAY),
+                3,
+                Duration::ZERO,
+            )
+            .unwrap();
+        assert!(delay > Duration::from_secs(3) && delay <= Duration::from_secs(7));
+    }
+
+    #[test]
+    fn round_trip_backoff() {
+        let policy = StandardRetryPolicy::recommended().with_backoff(crate::FibonacciBackoff {
+            base: Duration::from_secs(1),
+            cap: Duration::from_secs(30),
+        });
+        let json = serde_json::to_string(&policy).unwrap();
+        let restored: StandardRetryPolicy = serde_json::from_str(&json).unwrap();
+        assert_eq!(policy.backoff.to_config(), restored.backoff.to_config());
+        assert!(matches!(
+            restored.backoff.to_config(),
+            Some(BackoffConfig::Fibonacci(_))
+        ));
+
+        #[derive(Debug)]
+        struct Custom;
+
+        impl Backoff for Custom {
+            fn delay(&self, _attempt: u32, _previous: Duration) -> Duration {
+                Duration::from_secs(1)
+            }
+        }
+
+        assert!(serde_json::to_string(&policy.with_backoff(Custom)).is_err());
+    }
+
+    #[tokio::test]
+    async fn system_status_pause_gives_up() {
+        let pause = SystemStatusPause {
+            backoff: Arc::new(crate::FixedBackoff {
+                delay: Duration::from_millis(2),
+            }),
+            max_wait: Duration::from_millis(5),
+        };
+        let mut checks = 0;
+        assert!(
+            !pause
+                .wait_while(|| {
+                    checks += 1;
+                    async { true }
+                })
+                .await
+        );
+        assert_eq!(3, checks);
+        let mut issues = vec![false, true];
+        assert!(
+            pause
+                .wait_while(|| {
+                    let issue = issues.pop().unwrap();
+                    async move { issue }
+                })
+                .await
+        );
+    }
+
+    #[derive(Debug)]
+    struct RetryOnce;
+
+    impl RetryPolicy for RetryOnce {
+        fn retry_delay(&self, _error: &Error, retry: u32, _previous: Duration) -> Option<Duration> {
+            (retry == 1).then_some(Duration::ZERO)
+        }
+    }
+
+    #[test]
+    fn install_custom_policy() {
+        let client = SPN2Client::builder()
+            .anonymous()
+            .retry_policy(RetryOnce)
+            .build()
+            .unwrap();
+        assert_eq!("RetryOnce", client.effective_config().retry_policy);
+    }
+}
@@ -0,0 +1,77 @@
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time;
+
+use crate::{Error, SPN2Client};
+
+/// Tracks the capture sessions available to a user to delay captures while none are
+///
+/// SPN rejects capture requests with `error:user-session-limit` while all
+/// sessions of the user are busy. The available sessions are taken from the
+/// user status and counted down locally, and only requested again once they
+/// are used up.
+#[derive(Debug)]
+pub(crate) struct SessionLimiter {
+    poll_interval: Duration,
+    available: Mutex<Option<usize>>,
+}
+
+impl SessionLimiter {
+    pub(crate) fn new(poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            available: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Take a session if one is known to be available
+    fn try_take(&self) -> bool {
+        let mut available = self.available.lock().unwrap_or_else(|e| e.into_inner());
+        match available.as_mut() {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set the available sessions as reported by the API
+    fn refresh(&self, available: usize) {
+        *self.available.lock().unwrap_or_else(|e| e.into_inner()) = Some(available);
+    }
+
+    /// Wait until a session is available and take it
+    pub(crate) async fn acquire(&self, client: &SPN2Client) -> Result<(), Error> {
+        loop {
+            if self.try_take() {
+                return Ok(());
+            }
+            let status = client.get_user_status().await?;
+            self.refresh(status.available);
+            if self.try_take() {
+                return Ok(());
+            }
+            time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_down_sessions() {
+        let limiter = SessionLimiter::new(Duration::from_secs(5));
+        assert!(!limiter.try_take());
+        limiter.refresh(2);
+        assert!(limiter.try_take());
+        assert!(limiter.try_take());
+        assert!(!limiter.try_take());
+    }
+}
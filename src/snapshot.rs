@@ -0,0 +1,357 @@
+#[cfg(feature = "warc")]
+use std::io::{self, Write};
+
+use std::{
+    fmt,
+    ops::{Bound, RangeBounds},
+};
+
+use reqwest::{
+    header::{
+        HeaderMap, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE,
+    },
+    Method, RequestBuilder, StatusCode,
+};
+
+use crate::{Error, PlaybackMode, SPN2CaptureStatus, SPN2Client};
+
+/// A snapshot fetched from the Wayback Machine
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// The URL that was captured
+    pub original_url: String,
+    /// The Wayback timestamp of the capture, in YYYYMMDDHHMMSS format
+    pub timestamp: String,
+    /// The HTTP status of the archived response
+    pub status: StatusCode,
+    /// The headers of the archived response
+    pub headers: HeaderMap,
+    /// The body of the archived response
+    pub body: Vec<u8>,
+}
+
+/// The metadata of a snapshot, as returned by a HEAD request
+#[derive(Debug, Clone)]
+pub struct SnapshotInfo {
+    /// The HTTP status of the archived response
+    pub status: StatusCode,
+    /// The size of the archived response body in bytes, if known
+    pub content_length: Option<u64>,
+    /// The headers of the archived response
+    pub headers: HeaderMap,
+}
+
+impl SnapshotInfo {
+    /// Check whether the snapshot exists in the Wayback Machine
+    pub fn exists(&self) -> bool {
+        self.status.is_success() || self.status.is_redirection()
+    }
+}
+
+impl SPN2Client {
+    /// Fetch the snapshot of a successful capture from the Wayback Machine
+    ///
+    /// The snapshot is fetched in [`PlaybackMode::Raw`], so that it contains
+    /// the content as captured. Returns `None` if the capture did not succeed.
+    pub async fn fetch_snapshot(
+        &self,
+        status: &SPN2CaptureStatus,
+    ) -> Result<Option<Snapshot>, Error> {
+        self.fetch_snapshot_inner(status, HeaderMap::new()).await
+    }
+
+    /// Fetch a byte range of the snapshot of a successful capture
+    ///
+    /// Useful to check large captures without downloading them in full. If the
+    /// Wayback Machine honors the range, the snapshot has status
+    /// `206 Partial Content`. Returns `None` if the capture did not succeed.
+    ///
+    /// Fails with [`Error::InvalidRange`] if the range is empty.
+    pub async fn fetch_snapshot_range(
+        &self,
+        status: &SPN2CaptureStatus,
+        range: impl RangeBounds<u64> + fmt::Debug,
+    ) -> Result<Option<Snapshot>, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, range_header(range)?.parse().expect("valid header"));
+        self.fetch_snapshot_inner(status, headers).await
+    }
+
+    /// Fetch the snapshot of a successful capture unless it did not change
+    ///
+    /// Sends the `ETag` and `Last-Modified` of the headers of an earlier
+    /// fetch, e.g. [`Snapshot::headers`] or [`SnapshotInfo::headers`], as
+    /// `If-None-Match` and `If-Modified-Since`. If the snapshot did not
+    /// change, it has status `304 Not Modified` and an empty body. Returns
+    /// `None` if the capture did not succeed.
+    pub async fn fetch_snapshot_if_changed(
+        &self,
+        status: &SPN2CaptureStatus,
+        previous: &HeaderMap,
+    ) -> Result<Option<Snapshot>, Error> {
+        self.fetch_snapshot_inner(status, conditional_headers(previous))
+            .await
+    }
+
+    /// Get the metadata of the snapshot of a successful capture with a HEAD request
+    ///
+    /// Confirms that the snapshot exists and reports its size without
+    /// downloading it. Returns `None` if the capture did not succeed.
+    pub async fn head_snapshot(
+        &self,
+        status: &SPN2CaptureStatus,
+    ) -> Result<Option<SnapshotInfo>, Error> {
+        let Some(req) = self.snapshot_request(Method::HEAD, status) else {
+            return Ok(None);
+        };
+        let resp = req.send().await?;
+        let content_length = resp
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse().ok());
+        Ok(Some(SnapshotInfo {
+            status: resp.status(),
+            content_length,
+            headers: resp.headers().clone(),
+        }))
+    }
+
+    async fn fetch_snapshot_inner(
+        &self,
+        status: &SPN2CaptureStatus,
+        headers: HeaderMap,
+    ) -> Result<Option<Snapshot>, Error> {
+        let SPN2CaptureStatus::Success {
+            original_url,
+            timestamp,
+            ..
+        } = status
+        else {
+            return Ok(None);
+        };
+        let Some(req) = self.snapshot_request(Method::GET, status) else {
+            return Ok(None);
+        };
+        let resp = req.headers(headers).send().await?;
+        Ok(Some(Snapshot {
+            original_url: original_url.clone(),
+            timestamp: timestamp.clone(),
+            status: resp.status(),
+            headers: resp.headers().clone(),
+            body: resp.bytes().await?.to_vec(),
+        }))
+    }
+
+    /// Create a request for the raw playback of a successful capture
+    fn snapshot_request(
+        &self,
+        method: Method,
+        status: &SPN2CaptureStatus,
+    ) -> Option<RequestBuilder> {
+        let url = status.wayback_url_with(PlaybackMode::Raw)?;
+        Some(self.http_client.request(method, url).timeout(self.timeout))
+    }
+}
+
+/// Format a byte range as the value of a Range header
+///
+/// Fails if the range is empty, since a Range header can not express that.
+fn range_header(range: impl RangeBounds<u64> + fmt::Debug) -> Result<String, Error> {
+    let invalid = || Error::InvalidRange(format!("{range:?}"));
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).ok_or_else(invalid)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => Some(end),
+        Bound::Excluded(&end) => Some(end.checked_sub(1).ok_or_else(invalid)?),
+        Bound::Unbounded => None,
+    };
+    match end {
+        Some(end) if end < start => Err(invalid()),
+        Some(end) => Ok(format!("bytes={start}-{end}")),
+        None => Ok(format!("bytes={start}-")),
+    }
+}
+
+/// Get the headers of a request conditional on the validators of an earlier response
+fn conditional_headers(previous: &HeaderMap) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(etag) = previous.get(ETAG) {
+        headers.insert(IF_NONE_MATCH, etag.clone());
+    }
+    if let Some(last_modified) = previous.get(LAST_MODIFIED) {
+        headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+    }
+    headers
+}
+
+#[cfg(feature = "warc")]
+impl Snapshot {
+    /// Write the snapshot as a WARC response record
+    ///
+    /// The record's WARC-Target-URI is the captured URL and its WARC-Date the
+    /// capture timestamp. The archived headers are taken from the
+    /// `x-archive-orig-*` headers of the playback, the headers the Wayback
+    /// Machine adds itself are left out. Fails for snapshots whose status is
+    /// not `200 OK` or `206 Partial Content`, since their body is not the
+    /// archived content.
+    pub fn write_warc(&self, mut writer: impl Write) -> io::Result<()> {
+        let date = warc_date(&self.timestamp).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid timestamp {}", self.timestamp),
+            )
+        })?;
+        if !matches!(self.status, StatusCode::OK | StatusCode::PARTIAL_CONTENT) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("snapshot has status {}", self.status),
+            ));
+        }
+        let mut block = format!(
+            "HTTP/1.1 {} {}\r\n",
+            self.status.as_u16(),
+            self.status.canonical_reason().unwrap_or_default()
+        )
+        .into_bytes();
+        for (name, value) in &self.headers {
+            let Some(name) = name.as_str().strip_prefix(ARCHIVED_HEADER_PREFIX) else {
+                continue;
+            };
+            // the body was received unchunked
+            if name == "transfer-encoding" {
+                continue;
+            }
+            block.extend_from_slice(name.as_bytes());
+            block.extend_from_slice(b": ");
+            block.extend_from_slice(value.as_bytes());
+            block.extend_from_slice(b"\r\n");
+        }
+        block.extend_from_slice(b"\r\n");
+        block.extend_from_slice(&self.body);
+
+        write!(writer, "WARC/1.1\r\n")?;
+        write!(writer, "WARC-Type: response\r\n")?;
+        write!(
+            writer,
+            "WARC-Record-ID: <urn:uuid:{}>\r\n",
+            uuid::Uuid::new_v4()
+        )?;
+        write!(writer, "WARC-Date: {date}\r\n")?;
+        write!(writer, "WARC-Target-URI: {}\r\n", self.original_url)?;
+        write!(
+            writer,
+            "Content-Type: application/http; msgtype=response\r\n"
+        )?;
+        write!(writer, "Content-Length: {}\r\n", block.len())?;
+        write!(writer, "\r\n")?;
+        writer.write_all(&block)?;
+        write!(writer, "\r\n\r\n")
+    }
+}
+
+/// The prefix of the archived headers in the raw playback of a snapshot
+#[cfg(feature = "warc")]
+const ARCHIVED_HEADER_PREFIX: &str = "x-archive-orig-";
+
+/// Convert a Wayback timestamp to a WARC date, e.g. 2022-10-02T12:44:00Z
+#[cfg(feature = "warc")]
+fn warc_date(timestamp: &str) -> Option<String> {
+    if timestamp.len() != 14 || !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let part = |range: std::ops::Range<usize>| &timestamp[range];
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        part(0..4),
+        part(4..6),
+        part(6..8),
+        part(8..10),
+        part(10..12),
+        part(12..14)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_range_header() {
+        assert_eq!("bytes=0-1023", range_header(0..1024).unwrap());
+        assert_eq!("bytes=100-199", range_header(100..=199).unwrap());
+        assert_eq!("bytes=512-", range_header(512..).unwrap());
+        assert_eq!("bytes=0-9", range_header(..10).unwrap());
+        assert_eq!("bytes=0-0", range_header(..=0).unwrap());
+    }
+
+    #[test]
+    fn reject_invalid_ranges() {
+        assert!(matches!(range_header(..0), Err(Error::InvalidRange(_))));
+        assert!(range_header(10..10).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 10..5;
+        assert!(range_header(reversed).is_err());
+        let excluded_max = (Bound::Excluded(u64::MAX), Bound::Unbounded);
+        assert!(range_header(excluded_max).is_err());
+        assert_eq!(
+            format!("bytes={max}-{max}", max = u64::MAX),
+            range_header(u64::MAX..=u64::MAX).unwrap()
+        );
+    }
+
+    #[test]
+    fn conditional_request_headers() {
+        use reqwest::header::HeaderValue;
+
+        let mut previous = HeaderMap::new();
+        previous.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        previous.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Sun, 02 Oct 2022 12:44:00 GMT"),
+        );
+        let headers = conditional_headers(&previous);
+        assert_eq!("\"abc\"", headers[IF_NONE_MATCH]);
+        assert_eq!("Sun, 02 Oct 2022 12:44:00 GMT", headers[IF_MODIFIED_SINCE]);
+        assert!(conditional_headers(&HeaderMap::new()).is_empty());
+    }
+
+    #[cfg(feature = "warc")]
+    #[test]
+    fn write_warc_record() {
+        use reqwest::header::{HeaderValue, CONTENT_TYPE};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        headers.insert(
+            "x-archive-orig-content-type",
+            HeaderValue::from_static("text/html"),
+        );
+        headers.insert(
+            "x-archive-orig-transfer-encoding",
+            HeaderValue::from_static("chunked"),
+        );
+        let mut snapshot = Snapshot {
+            original_url: "https://example.com/".to_string(),
+            timestamp: "20221002124400".to_string(),
+            status: StatusCode::OK,
+            headers,
+            body: b"<html></html>".to_vec(),
+        };
+        let mut out = Vec::new();
+        snapshot.write_warc(&mut out).unwrap();
+        let record = String::from_utf8(out).unwrap();
+        let block = "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n\r\n<html></html>";
+        assert!(record.starts_with("WARC/1.1\r\nWARC-Type: response\r\n"));
+        assert!(record.contains("WARC-Date: 2022-10-02T12:44:00Z\r\n"));
+        assert!(record.contains("WARC-Target-URI: https://example.com/\r\n"));
+        assert!(record.contains(&format!("Content-Length: {}\r\n", block.len())));
+        assert!(record.ends_with(&format!("\r\n\r\n{block}\r\n\r\n")));
+        assert_eq!(None, warc_date("2022100212"));
+
+        snapshot.status = StatusCode::NOT_FOUND;
+        assert!(snapshot.write_warc(&mut Vec::new()).is_err());
+    }
+}
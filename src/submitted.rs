@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{inflight::normalize_url, JobId, SPN2CaptureRequestOptParams};
+
+/// How long a submitted job is remembered to recognize duplicate submissions
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// The capture jobs a client submitted recently
+///
+/// SPN returns the job ID of the running capture when a URL is submitted
+/// again. Seeing a job ID twice means that the capture was submitted twice
+/// but runs only once. When the response to a capture request got lost, its
+/// job ID is unknown, so the request is remembered by URL and parameters
+/// instead, and the next answered request for them counts as a duplicate.
+#[derive(Default)]
+pub(crate) struct SubmittedJobs(Mutex<Submitted>);
+
+#[derive(Default)]
+struct Submitted {
+    jobs: HashMap<JobId, Instant>,
+    unanswered: Vec<(String, SPN2CaptureRequestOptParams, Instant)>,
+}
+
+impl SubmittedJobs {
+    /// Record a submitted job, returning whether it was submitted recently already
+    pub(crate) fn record(&self, job_id: &JobId) -> bool {
+        let now = Instant::now();
+        let mut submitted = self.lock(now);
+        submitted.jobs.insert(job_id.clone(), now).is_some()
+    }
+
+    /// Record a capture request that may have reached SPN but got no response
+    pub(crate) fn record_unanswered(&self, url: &str, opt_params: &SPN2CaptureRequestOptParams) {
+        let now = Instant::now();
+        let mut submitted = self.lock(now);
+        submitted
+            .unanswered
+            .push((normalize_url(url), opt_params.clone(), now));
+    }
+
+    /// Forget the unanswered requests for a URL, returning whether there were any
+    pub(crate) fn take_unanswered(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+    ) -> bool {
+        let url = normalize_url(url);
+        let mut submitted = self.lock(Instant::now());
+        let before = submitted.unanswered.len();
+        submitted
+            .unanswered
+            .retain(|(other_url, other_params, _)| (other_url, other_params) != (&url, opt_params));
+        submitted.unanswered.len() < before
+    }
+
+    /// Lock the records, dropping those that are older than the window
+    fn lock(&self, now: Instant) -> MutexGuard<'_, Submitted> {
+        let mut submitted = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let recent = |at: &Instant| now.duration_since(*at) < DUPLICATE_WINDOW;
+        submitted
+            .jobs
+            .retain(|_, submitted_at| recent(submitted_at));
+        submitted
+            .unanswered
+            .retain(|(_, _, sent_at)| recent(sent_at));
+        submitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognize_duplicates() {
+        let jobs = SubmittedJobs::default();
+        let job_id: JobId = "spn2-abc".parse().unwrap();
+        assert!(!jobs.record(&job_id));
+        assert!(jobs.record(&job_id));
+        assert!(!jobs.record(&"spn2-def".parse().unwrap()));
+    }
+
+    #[test]
+    fn recognize_unanswered_requests() {
+        let jobs = SubmittedJobs::default();
+        let params = SPN2CaptureRequestOptParams::default();
+        jobs.record_unanswered("https://example.com/", &params);
+        let other_params = SPN2CaptureRequestOptParams {
+            capture_all: true,
+            ..Default::default()
+        };
+        assert!(!jobs.take_unanswered("https://example.com/", &other_params));
+        assert!(jobs.take_unanswered("https://EXAMPLE.com/#top", &params));
+        assert!(!jobs.take_unanswered("https://example.com/", &params));
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::Serialize;
+
+use crate::{JobId, SPN2CaptureStatus};
+
+/// An observed change in the state of a capture job
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TimelineEvent {
+    /// The capture was requested
+    Submitted,
+    /// The job was observed pending with the given amount of captured resources
+    Pending {
+        /// Amount of resources captured so far
+        resources: usize,
+    },
+    /// The job was observed failed
+    Error {
+        /// The error code of the job
+        status_ext: String,
+    },
+    /// The job was observed succeeded
+    Success,
+}
+
+/// An entry in the timeline of a capture job
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// When the change was observed
+    pub observed_at: SystemTime,
+    /// What changed
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+/// How long the timeline of a job is kept after its last recorded change
+const RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// In-memory record of the timelines of all jobs a client observed recently
+#[derive(Default)]
+pub(crate) struct Timelines(Mutex<HashMap<JobId, Timeline>>);
+
+#[derive(Default)]
+struct Timeline {
+    entries: Vec<TimelineEntry>,
+    changed_at: Option<Instant>,
+}
+
+impl Timelines {
+    pub(crate) fn record_submitted(&self, job_id: &JobId) {
+        self.record(job_id, TimelineEvent::Submitted);
+    }
+
+    pub(crate) fn record_status(&self, job_id: &JobId, status: &SPN2CaptureStatus) {
+        let event = match status {
+            SPN2CaptureStatus::Pending { resources, .. } => TimelineEvent::Pending {
+                resources: resources.len(),
+            },
+            SPN2CaptureStatus::Error { status_ext, .. } => TimelineEvent::Error {
+                status_ext: status_ext.clone(),
+            },
+            SPN2CaptureStatus::Success { .. } => TimelineEvent::Success,
+        };
+        self.record(job_id, event);
+    }
+
+    /// Record the event, unless it equals the last recorded event of the job
+    fn record(&self, job_id: &JobId, event: TimelineEvent) {
+        let mut timelines = self.lock();
+        let timeline = timelines.entry(job_id.clone()).or_default();
+        if timeline.entries.last().map(|entry| &entry.event) != Some(&event) {
+            timeline.entries.push(TimelineEntry {
+                observed_at: SystemTime::now(),
+                event,
+            });
+            timeline.changed_at = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn get(&self, job_id: &JobId) -> Vec<TimelineEntry> {
+        let timelines = self.lock();
+        timelines
+            .get(job_id)
+            .map(|timeline| timeline.entries.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn remove(&self, job_id: &JobId) -> Vec<TimelineEntry> {
+        let mut timelines = self.lock();
+        timelines
+            .remove(job_id)
+            .map(|timeline| timeline.entries)
+            .unwrap_or_default()
+    }
+
+    /// Lock the timelines, dropping those that did not change within the retention
+    fn lock(&self) -> MutexGuard<'_, HashMap<JobId, Timeline>> {
+        let mut timelines = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        timelines.retain(|_, timeline| {
+            timeline
+                .changed_at
+                .is_some_and(|changed_at| changed_at.elapsed() < RETENTION)
+        });
+        timelines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_only_changes() {
+        let timelines = Timelines::default();
+        let job_id: JobId = "spn2-abc".parse().unwrap();
+        let pending = |n: usize| SPN2CaptureStatus::Pending {
+            resources: vec!["https://example.com/".to_string(); n],
+            extra: Default::default(),
+        };
+        timelines.record_submitted(&job_id);
+        timelines.record_status(&job_id, &pending(0));
+        timelines.record_status(&job_id, &pending(0));
+        timelines.record_status(&job_id, &pending(2));
+        let events: Vec<_> = timelines
+            .get(&job_id)
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        assert_eq!(
+            vec![
+                TimelineEvent::Submitted,
+                TimelineEvent::Pending { resources: 0 },
+                TimelineEvent::Pending { resources: 2 },
+            ],
+            events
+        );
+        assert_eq!(3, timelines.remove(&job_id).len());
+        assert!(timelines.get(&job_id).is_empty());
+    }
+
+    #[test]
+    fn drop_old_timelines() {
+        let timelines = Timelines::default();
+        let job_id: JobId = "spn2-abc".parse().unwrap();
+        timelines.record_submitted(&job_id);
+        if let Some(changed_at) = Instant::now().checked_sub(RETENTION) {
+            timelines
+                .0
+                .lock()
+                .unwrap()
+                .get_mut(&job_id)
+                .unwrap()
+                .changed_at = Some(changed_at);
+            assert!(timelines.get(&job_id).is_empty());
+        }
+    }
+}
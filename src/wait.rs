@@ -0,0 +1,822 @@
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    Backoff, CaptureError, CaptureHandle, CaptureOutcome, Error, JobId,
+    SPN2CaptureRequestOptParams, SPN2CaptureStatus, SPN2Client, ScheduleBackoff, SpnApi,
+};
+
+/// The default interval in which the status of a capture job is polled
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Options for waiting until a capture job is finished
+///
+/// By default, the status is polled after 2s, 5s and then every 10s, see
+/// [`ScheduleBackoff`]. Converting a [`Duration`] into options polls in that
+/// fixed interval instead.
+///
+/// # Examples
+///
+/// Give up on jobs that are still pending after 10 minutes:
+/// ```
+/// use std::time::Duration;
+///
+/// let wait_opts = spn::WaitOptions {
+///     max_pending_age: Some(Duration::from_secs(600)),
+///     stale_pending: spn::StalePendingPolicy::Abandon,
+///     ..Default::default()
+/// };
+/// ```
+///
+/// Poll with exponentially growing delays:
+/// ```
+/// let wait_opts = spn::WaitOptions::default().with_backoff(spn::ExponentialBackoff::default());
+/// ```
+///
+/// Report progress on every poll:
+/// ```
+/// let wait_opts = spn::WaitOptions::default().on_progress(|progress| {
+///     println!("{} resources after {:?}", progress.resources.len(), progress.elapsed);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct WaitOptions {
+    /// The interval in which the capture status is polled, if no backoff is set
+    pub poll_interval: Duration,
+    /// The strategy for the delays between polls, overrides the poll interval
+    pub backoff: Option<Arc<dyn Backoff>>,
+    /// A custom polling cadence, overrides the backoff and the poll interval
+    pub poll_strategy: Option<Arc<dyn PollStrategy>>,
+    /// How long a job may stay pending before it is considered stale
+    pub max_pending_age: Option<Duration>,
+    /// What to do with a job that is considered stale
+    pub stale_pending: StalePendingPolicy,
+    /// How long to wait at most, counted from the capture request
+    ///
+    /// If the job is still pending after the deadline, waiting fails with
+    /// [`Error::TimedOut`] which contains the last observed status.
+    pub deadline: Option<Duration>,
+    /// How often the status is polled at most
+    ///
+    /// If the job is still pending after this many polls, waiting fails with
+    /// [`Error::PendingLimitReached`]. Keeps unattended pipelines from polling
+    /// a wedged job forever even without a deadline.
+    pub max_polls: Option<u32>,
+    /// Cancels waiting when triggered
+    ///
+    /// Waiting then fails with [`Error::Cancelled`] which contains the job ID,
+    /// so that the job can be resumed later with [`SPN2Client::handle_for`].
+    pub cancellation_token: Option<CancellationToken>,
+    /// Called on every poll that finds the job still pending
+    pub progress_callback: Option<ProgressCallback>,
+    /// How often [`SPN2Client::capture_and_wait`] resubmits a capture that
+    /// failed with a transient error
+    ///
+    /// Errors like `error:no-browsers-available` usually resolve within
+    /// minutes, see [`SPN2CaptureStatus::is_transient_error`]. Resubmits are
+    /// spaced by the poll interval.
+    pub transient_resubmits: u32,
+}
+
+/// A custom cadence for polling the status of pending capture jobs
+///
+/// Unlike a [`Backoff`], a strategy sees the last pending status and can give
+/// up on a job.
+///
+/// # Examples
+///
+/// Poll every second while resources are still being captured, and every
+/// 10 seconds otherwise:
+/// ```
+/// use std::time::Duration;
+///
+/// use spn::{PollState, PollStrategy, SPN2CaptureStatus};
+///
+/// #[derive(Debug)]
+/// struct FollowResources;
+///
+/// impl PollStrategy for FollowResources {
+///     fn next_delay(&self, state: &PollState<'_>) -> Option<Duration> {
+///         match state.status {
+///             SPN2CaptureStatus::Pending { resources, .. } if !resources.is_empty() => {
+///                 Some(Duration::from_secs(1))
+///             }
+///             _ => Some(Duration::from_secs(10)),
+///         }
+///     }
+/// }
+///
+/// let wait_opts = spn::WaitOptions::default().with_poll_strategy(FollowResources);
+/// ```
+pub trait PollStrategy: fmt::Debug + Send + Sync {
+    /// Get the delay before the next poll of a pending job
+    ///
+    /// Returning `None` gives up on the job, waiting then fails with
+    /// [`Error::PendingLimitReached`].
+    fn next_delay(&self, state: &PollState<'_>) -> Option<Duration>;
+}
+
+/// The state of a pending capture job, as seen by a [`PollStrategy`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PollState<'a> {
+    /// The time elapsed since the capture was requested
+    pub elapsed: Duration,
+    /// The amount of status polls so far
+    pub polls: u32,
+    /// The delay before the last poll, zero before the first one
+    pub previous_delay: Duration,
+    /// The last observed status
+    pub status: &'a SPN2CaptureStatus,
+}
+
+/// A callback that is invoked with the progress of a pending capture job
+pub type ProgressCallback = Arc<dyn Fn(&Progress<'_>) + Send + Sync>;
+
+/// The progress of a pending capture job
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Progress<'a> {
+    /// The ID of the capture job
+    pub job_id: &'a JobId,
+    /// The time elapsed since the capture was requested
+    pub elapsed: Duration,
+    /// The amount of status polls so far
+    pub polls: u32,
+    /// The resources captured so far
+    pub resources: &'a [String],
+}
+
+impl WaitOptions {
+    /// Set the strategy for the delays between polls
+    pub fn with_backoff(mut self, backoff: impl Backoff + 'static) -> Self {
+        self.backoff = Some(Arc::new(backoff));
+        self
+    }
+
+    /// Set a custom polling cadence
+    pub fn with_poll_strategy(mut self, strategy: impl PollStrategy + 'static) -> Self {
+        self.poll_strategy = Some(Arc::new(strategy));
+        self
+    }
+
+    /// Set the callback that is invoked on every poll that finds the job still pending
+    pub fn on_progress(mut self, callback: impl Fn(&Progress<'_>) + Send + Sync + 'static) -> Self {
+        self.progress_callback = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            backoff: Some(Arc::new(ScheduleBackoff::default())),
+            poll_strategy: None,
+            max_pending_age: None,
+            stale_pending: StalePendingPolicy::default(),
+            deadline: None,
+            max_polls: None,
+            cancellation_token: None,
+            progress_callback: None,
+            transient_resubmits: 0,
+        }
+    }
+}
+
+impl fmt::Debug for WaitOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitOptions")
+            .field("poll_interval", &self.poll_interval)
+            .field("backoff", &self.backoff)
+            .field("poll_strategy", &self.poll_strategy)
+            .field("max_pending_age", &self.max_pending_age)
+            .field("stale_pending", &self.stale_pending)
+            .field("deadline", &self.deadline)
+            .field("max_polls", &self.max_polls)
+            .field("cancellation_token", &self.cancellation_token)
+            .field("progress_callback", &self.progress_callback.is_some())
+            .field("transient_resubmits", &self.transient_resubmits)
+            .finish()
+    }
+}
+
+impl From<Duration> for WaitOptions {
+    fn from(poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            backoff: None,
+            ..Default::default()
+        }
+    }
+}
+
+/// What to do with a job that stays pending for longer than the maximum pending age
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StalePendingPolicy {
+    /// Keep waiting for the job to finish
+    #[default]
+    KeepWaiting,
+    /// Stop waiting, the outcome contains the last pending status
+    Abandon,
+    /// Submit the URL again and wait for the new job
+    ///
+    /// If the new job becomes stale as well it is abandoned.
+    ResubmitOnce,
+}
+
+impl SPN2Client {
+    /// Capture the given URL and wait until the capture job is finished
+    ///
+    /// See [`CaptureHandle::wait`] for how the job is waited on. Failures are
+    /// distinguished by whether submitting, the capture job itself or
+    /// tracking the job failed. Captures that fail with a transient error are
+    /// resubmitted up to [`WaitOptions::transient_resubmits`] times. The
+    /// deadline and the maximum polls apply to all submissions together.
+    pub async fn capture_and_wait(
+        &self,
+        url: &str,
+        opt_params: &SPN2CaptureRequestOptParams,
+        wait_opts: &WaitOptions,
+    ) -> Result<CaptureOutcome, CaptureError> {
+        capture_and_wait_with(self, url, opt_params, wait_opts).await
+    }
+}
+
+impl CaptureHandle<'_> {
+    /// Wait until the capture job is finished
+    ///
+    /// The capture status is polled until it is not pending anymore, or until
+    /// the job is abandoned because it stayed pending for too long.
+    /// Fails with [`Error::TimedOut`] if the deadline passes, with
+    /// [`Error::PendingLimitReached`] if the job is still pending after the
+    /// maximum amount of polls and with [`Error::Cancelled`] if the
+    /// cancellation token is triggered.
+    pub async fn wait(&self, wait_opts: &WaitOptions) -> Result<CaptureOutcome, Error> {
+        let submitted_at = time::Instant::from_std(self.submitted_at);
+        wait_for_job(
+            self.client,
+            &self.url,
+            &self.opt_params,
+            self.job_id().clone(),
+            submitted_at,
+            wait_opts,
+            &mut WaitBudget::new(submitted_at),
+        )
+        .await
+    }
+}
+
+/// The deadline and poll budget of waiting, carried across resubmitted captures
+#[derive(Debug)]
+struct WaitBudget {
+    /// When the first capture was requested, the deadline counts from here
+    started_at: time::Instant,
+    /// The amount of status polls so far
+    polls: u32,
+}
+
+impl WaitBudget {
+    fn new(started_at: time::Instant) -> Self {
+        Self {
+            started_at,
+            polls: 0,
+        }
+    }
+
+    /// Get the time left until the deadline, `None` if it passed
+    fn remaining(&self, wait_opts: &WaitOptions) -> Option<Duration> {
+        match wait_opts.deadline {
+            Some(deadline) => deadline
+                .checked_sub(self.started_at.elapsed())
+                .filter(|remaining| !remaining.is_zero()),
+            None => Some(Duration::MAX),
+        }
+    }
+
+    /// Check whether a job can still be submitted and polled after the given delay
+    fn allows_resubmit(&self, wait_opts: &WaitOptions, delay: Duration) -> bool {
+        let polls_left = wait_opts.max_polls.is_none_or(|max| self.polls < max);
+        polls_left && self.remaining(wait_opts).is_some_and(|left| left > delay)
+    }
+}
+
+/// Submit the capture and wait for its job, resubmitting it on transient errors
+async fn capture_and_wait_with(
+    api: &impl SpnApi,
+    url: &str,
+    opt_params: &SPN2CaptureRequestOptParams,
+    wait_opts: &WaitOptions,
+) -> Result<CaptureOutcome, CaptureError> {
+    let mut budget = WaitBudget::new(time::Instant::now());
+    let token = wait_opts.cancellation_token.as_ref();
+    let mut resubmits = 0;
+    loop {
+        let job_id = api
+            .submit_capture(url, opt_params)
+            .await
+            .map_err(CaptureError::SubmitFailed)?
+            .job_id;
+        let tracking_failed = |source| CaptureError::TrackingFailed {
+            job_id: job_id.clone(),
+            source,
+        };
+        let outcome = wait_for_job(
+            api,
+            url,
+            opt_params,
+            job_id.clone(),
+            time::Instant::now(),
+            wait_opts,
+            &mut budget,
+        )
+        .await
+        .map_err(tracking_failed)?;
+        match outcome.status {
+            SPN2CaptureStatus::Error { .. }
+                if outcome.status.is_transient_error()
+                    && resubmits < wait_opts.transient_resubmits
+                    && budget.allows_resubmit(wait_opts, wait_opts.poll_interval) =>
+            {
+                resubmits += 1;
+                if cancellable(token, time::sleep(wait_opts.poll_interval))
+                    .await
+                    .is_none()
+                {
+                    return Err(tracking_failed(Error::Cancelled {
+                        job_id: outcome.job_id,
+                    }));
+                }
+            }
+            SPN2CaptureStatus::Error { .. } => {
+                return Err(CaptureError::CaptureFailed(Box::new(outcome)))
+            }
+            _ => return Ok(outcome),
+        }
+    }
+}
+
+/// Poll the status of the job until it is finished, using up the budget
+async fn wait_for_job(
+    api: &impl SpnApi,
+    url: &str,
+    opt_params: &SPN2CaptureRequestOptParams,
+    mut job_id: JobId,
+    mut submitted_at: time::Instant,
+    wait_opts: &WaitOptions,
+    budget: &mut WaitBudget,
+) -> Result<CaptureOutcome, Error> {
+    let mut stale_pending = None;
+    let mut poll_interval = Duration::ZERO;
+    let token = wait_opts.cancellation_token.as_ref();
+    loop {
+        let Some(status) = cancellable(token, api.get_capture_status(&job_id)).await else {
+            return Err(Error::Cancelled { job_id });
+        };
+        let status = status?;
+        budget.polls += 1;
+        let polls = budget.polls;
+        let SPN2CaptureStatus::Pending { resources, .. } = &status else {
+            return Ok(CaptureOutcome::new(
+                url.to_string(),
+                job_id,
+                status,
+                stale_pending,
+                opt_params,
+            ));
+        };
+        if let Some(callback) = &wait_opts.progress_callback {
+            callback(&Progress {
+                job_id: &job_id,
+                elapsed: budget.started_at.elapsed(),
+                polls,
+                resources,
+            });
+        }
+        let is_stale = wait_opts
+            .max_pending_age
+            .is_some_and(|age| submitted_at.elapsed() > age);
+        if is_stale {
+            match (wait_opts.stale_pending, stale_pending) {
+                (StalePendingPolicy::KeepWaiting, _) => {
+                    stale_pending = Some(StalePendingPolicy::KeepWaiting);
+                }
+                (StalePendingPolicy::ResubmitOnce, None) => {
+                    let resubmit = api.submit_capture(url, opt_params);
+                    let Some(response) = cancellable(token, resubmit).await else {
+                        return Err(Error::Cancelled { job_id });
+                    };
+                    job_id = response?.job_id;
+                    submitted_at = time::Instant::now();
+                    stale_pending = Some(StalePendingPolicy::ResubmitOnce);
+                }
+                (policy, _) => {
+                    return Ok(CaptureOutcome::new(
+                        url.to_string(),
+                        job_id,
+                        status,
+                        Some(stale_pending.unwrap_or(policy)),
+                        opt_params,
+                    ));
+                }
+            }
+        }
+        if wait_opts.max_polls.is_some_and(|max| polls >= max) {
+            return Err(Error::PendingLimitReached {
+                job_id,
+                polls,
+                elapsed: budget.started_at.elapsed(),
+            });
+        }
+        poll_interval = match (&wait_opts.poll_strategy, &wait_opts.backoff) {
+            (Some(strategy), _) => {
+                let state = PollState {
+                    elapsed: budget.started_at.elapsed(),
+                    polls,
+                    previous_delay: poll_interval,
+                    status: &status,
+                };
+                let Some(delay) = strategy.next_delay(&state) else {
+                    return Err(Error::PendingLimitReached {
+                        job_id,
+                        polls,
+                        elapsed: state.elapsed,
+                    });
+                };
+                delay
+            }
+            (None, Some(backoff)) => backoff.delay(polls, poll_interval),
+            (None, None) => wait_opts.poll_interval,
+        };
+        let Some(remaining) = budget.remaining(wait_opts) else {
+            return Err(Error::TimedOut {
+                job_id,
+                last_status: Box::new(status),
+            });
+        };
+        if cancellable(token, time::sleep(poll_interval.min(remaining)))
+            .await
+            .is_none()
+        {
+            return Err(Error::Cancelled { job_id });
+        }
+    }
+}
+
+/// Run the future to completion, unless the token is cancelled first
+async fn cancellable<F: Future>(token: Option<&CancellationToken>, fut: F) -> Option<F::Output> {
+    match token {
+        // a cancelled token wins over a ready future
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => None,
+            output = fut => Some(output),
+        },
+        None => Some(fut.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use crate::{SPN2CaptureResponse, SPN2SystemStatus, SPN2UserStatus};
+
+    use super::*;
+
+    /// An API that answers polls with canned statuses, and with pending ones after them
+    #[derive(Default)]
+    struct CannedApi {
+        statuses: Mutex<VecDeque<SPN2CaptureStatus>>,
+        submits: Mutex<u32>,
+        polls: Mutex<u32>,
+        /// Cancelled on every status poll, if set
+        cancel_on_poll: Option<CancellationToken>,
+    }
+
+    impl CannedApi {
+        fn new(statuses: impl IntoIterator<Item = SPN2CaptureStatus>) -> Self {
+            Self {
+                statuses: Mutex::new(statuses.into_iter().collect()),
+                ..Default::default()
+            }
+        }
+
+        fn submits(&self) -> u32 {
+            *self.submits.lock().unwrap()
+        }
+
+        fn polls(&self) -> u32 {
+            *self.polls.lock().unwrap()
+        }
+    }
+
+    impl SpnApi for CannedApi {
+        async fn submit_capture(
+            &self,
+            url: &str,
+            _opt_params: &SPN2CaptureRequestOptParams,
+        ) -> Result<SPN2CaptureResponse, Error> {
+            let mut submits = self.submits.lock().unwrap();
+            *submits += 1;
+            Ok(SPN2CaptureResponse {
+                url: url.to_string(),
+                job_id: format!("job-{submits}").parse()?,
+                message: None,
+                extra: Default::default(),
+            })
+        }
+
+        async fn get_capture_status(&self, _job_id: &JobId) -> Result<SPN2CaptureStatus, Error> {
+            *self.polls.lock().unwrap() += 1;
+            if let Some(token) = &self.cancel_on_poll {
+                token.cancel();
+            }
+            let next = self.statuses.lock().unwrap().pop_front();
+            Ok(next.unwrap_or_else(|| status(r#"{"status":"pending"}"#)))
+        }
+
+        async fn get_user_status(&self) -> Result<SPN2UserStatus, Error> {
+            unreachable!("waiting does not check the user status")
+        }
+
+        async fn get_system_status(&self) -> Result<SPN2SystemStatus, Error> {
+            unreachable!("waiting does not check the system status")
+        }
+    }
+
+    fn status(json: &str) -> SPN2CaptureStatus {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn transient_error() -> SPN2CaptureStatus {
+        status(
+            r#"{"status":"error","status_ext":"error:no-browsers-available",
+                "message":"no browsers available"}"#,
+        )
+    }
+
+    fn success() -> SPN2CaptureStatus {
+        status(
+            r#"{"status":"success","original_url":"https://example.com/",
+                "timestamp":"20221002124400","duration_sec":5}"#,
+        )
+    }
+
+    /// Wait for the job `job-0` that was just submitted
+    async fn wait(api: &CannedApi, wait_opts: &WaitOptions) -> Result<CaptureOutcome, Error> {
+        let now = time::Instant::now();
+        wait_for_job(
+            api,
+            "https://example.com/",
+            &Default::default(),
+            "job-0".parse().unwrap(),
+            now,
+            wait_opts,
+            &mut WaitBudget::new(now),
+        )
+        .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn time_out_at_deadline() {
+        let api = CannedApi::default();
+        let wait_opts = WaitOptions {
+            deadline: Some(Duration::from_secs(10)),
+            ..Duration::from_secs(4).into()
+        };
+        let started_at = time::Instant::now();
+        let result = wait(&api, &wait_opts).await;
+        assert!(matches!(result, Err(Error::TimedOut { .. })));
+        // polled after 0s, 4s, 8s and at the deadline
+        assert_eq!(4, api.polls());
+        assert_eq!(Duration::from_secs(10), started_at.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limit_polls() {
+        let api = CannedApi::default();
+        let wait_opts = WaitOptions {
+            max_polls: Some(3),
+            ..Duration::from_secs(1).into()
+        };
+        let Err(Error::PendingLimitReached { polls, elapsed, .. }) = wait(&api, &wait_opts).await
+        else {
+            panic!("polling must be limited");
+        };
+        assert_eq!(3, polls);
+        assert_eq!(Duration::from_secs(2), elapsed);
+        assert_eq!(3, api.polls());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn report_progress() {
+        let api = CannedApi::new([
+            status(r#"{"status":"pending","resources":["https://example.com/"]}"#),
+            status(r#"{"status":"pending","resources":["https://example.com/","a.js"]}"#),
+            success(),
+        ]);
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let wait_opts = WaitOptions::from(Duration::from_secs(2)).on_progress(move |progress| {
+            recorded.lock().unwrap().push((
+                progress.job_id.to_string(),
+                progress.polls,
+                progress.elapsed,
+                progress.resources.len(),
+            ));
+        });
+        let outcome = wait(&api, &wait_opts).await.unwrap();
+        assert!(outcome.is_success());
+        let job_id = "job-0".to_string();
+        assert_eq!(
+            vec![
+                (job_id.clone(), 1, Duration::ZERO, 1),
+                (job_id, 2, Duration::from_secs(2), 2)
+            ],
+            *reports.lock().unwrap()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_stale_jobs() {
+        let stale_opts = |policy| WaitOptions {
+            max_pending_age: Some(Duration::from_secs(5)),
+            stale_pending: policy,
+            ..Duration::from_secs(2).into()
+        };
+
+        // stale after the poll at 6s
+        let api = CannedApi::default();
+        let outcome = wait(&api, &stale_opts(StalePendingPolicy::Abandon))
+            .await
+            .unwrap();
+        assert_eq!(Some(StalePendingPolicy::Abandon), outcome.stale_pending);
+        assert!(matches!(outcome.status, SPN2CaptureStatus::Pending { .. }));
+        assert_eq!(4, api.polls());
+
+        // resubmitted at 6s, the new job is stale after the poll at 12s
+        let api = CannedApi::default();
+        let outcome = wait(&api, &stale_opts(StalePendingPolicy::ResubmitOnce))
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(StalePendingPolicy::ResubmitOnce),
+            outcome.stale_pending
+        );
+        assert_eq!("job-1", outcome.job_id.as_str());
+        assert_eq!(1, api.submits());
+        assert_eq!(7, api.polls());
+
+        let api = CannedApi::new([
+            status(r#"{"status":"pending"}"#),
+            status(r#"{"status":"pending"}"#),
+            status(r#"{"status":"pending"}"#),
+            status(r#"{"status":"pending"}"#),
+            success(),
+        ]);
+        let outcome = wait(&api, &stale_opts(StalePendingPolicy::KeepWaiting))
+            .await
+            .unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(Some(StalePendingPolicy::KeepWaiting), outcome.stale_pending);
+        assert_eq!(0, api.submits());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn follow_poll_strategy() {
+        /// Waits one second longer before every poll, gives up after three polls
+        #[derive(Debug, Default)]
+        struct Slowing(Mutex<Vec<Duration>>);
+
+        impl PollStrategy for Slowing {
+            fn next_delay(&self, state: &PollState<'_>) -> Option<Duration> {
+                self.0.lock().unwrap().push(state.previous_delay);
+                (state.polls < 3).then(|| Duration::from_secs(state.polls.into()))
+            }
+        }
+
+        let strategy = Arc::new(Slowing::default());
+        let wait_opts = WaitOptions {
+            poll_strategy: Some(strategy.clone()),
+            ..Default::default()
+        };
+        let api = CannedApi::default();
+        let Err(Error::PendingLimitReached { polls, elapsed, .. }) = wait(&api, &wait_opts).await
+        else {
+            panic!("the strategy must give up");
+        };
+        assert_eq!(3, polls);
+        assert_eq!(Duration::from_secs(3), elapsed);
+        assert_eq!(
+            vec![
+                Duration::ZERO,
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ],
+            *strategy.0.lock().unwrap()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resubmit_transient_errors() {
+        let capture = |api, transient_resubmits| async move {
+            let wait_opts = WaitOptions {
+                transient_resubmits,
+                ..Duration::from_secs(5).into()
+            };
+            capture_and_wait_with(api, "https://example.com/", &Default::default(), &wait_opts)
+                .await
+        };
+        let api = CannedApi::new([transient_error(), success()]);
+        let outcome = capture(&api, 1).await.unwrap();
+        assert!(outcome.is_success());
+        assert_eq!("job-2", outcome.job_id.as_str());
+        assert_eq!(2, api.submits());
+
+        let api = CannedApi::new([transient_error(), success()]);
+        assert!(matches!(
+            capture(&api, 0).await,
+            Err(CaptureError::CaptureFailed(_))
+        ));
+        assert_eq!(1, api.submits());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancel_between_resubmits() {
+        let token = CancellationToken::new();
+        let api = CannedApi {
+            cancel_on_poll: Some(token.clone()),
+            ..CannedApi::new([transient_error()])
+        };
+        let wait_opts = WaitOptions {
+            transient_resubmits: 3,
+            cancellation_token: Some(token),
+            ..Duration::from_secs(5).into()
+        };
+        let result = capture_and_wait_with(
+            &api,
+            "https://example.com/",
+            &Default::default(),
+            &wait_opts,
+        )
+        .await;
+        let Err(CaptureError::TrackingFailed { source, .. }) = result else {
+            panic!("waiting must be cancelled");
+        };
+        assert!(matches!(source, Error::Cancelled { .. }));
+        assert_eq!(1, api.submits());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn share_deadline_across_resubmits() {
+        let api = CannedApi::new(std::iter::repeat_n(transient_error(), 10));
+        let wait_opts = WaitOptions {
+            transient_resubmits: 10,
+            deadline: Some(Duration::from_secs(10)),
+            ..Duration::from_secs(4).into()
+        };
+        let started_at = time::Instant::now();
+        let result = capture_and_wait_with(
+            &api,
+            "https://example.com/",
+            &Default::default(),
+            &wait_opts,
+        )
+        .await;
+        assert!(matches!(result, Err(CaptureError::CaptureFailed(_))));
+        // submitted after 0s, 4s and 8s, a resubmit after 12s would miss the deadline
+        assert_eq!(3, api.submits());
+        assert_eq!(Duration::from_secs(8), started_at.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn share_max_polls_across_resubmits() {
+        let api = CannedApi::new([transient_error(), transient_error()]);
+        let wait_opts = WaitOptions {
+            transient_resubmits: 10,
+            max_polls: Some(3),
+            ..Duration::from_secs(1).into()
+        };
+        let result = capture_and_wait_with(
+            &api,
+            "https://example.com/",
+            &Default::default(),
+            &wait_opts,
+        )
+        .await;
+        let Err(CaptureError::TrackingFailed { source, .. }) = result else {
+            panic!("polling must be limited");
+        };
+        assert!(matches!(
+            source,
+            Error::PendingLimitReached { polls: 3, .. }
+        ));
+        assert_eq!(3, api.polls());
+        assert_eq!(3, api.submits());
+    }
+}